@@ -1,111 +1,545 @@
-use mollusk_svm::{program, Mollusk};
+//! Compute units of every stake instruction, in the states it is commonly
+//! used in, next to those of the native stake program.
+//!
+//! Run with `cargo build-sbf && cargo bench`. `MolluskComputeUnitBencher` adds a
+//! table to `benches/compute_units.md` whenever the numbers change, and one for
+//! the pinned native program to `benches/native/compute_units.md`. The most
+//! recent table is the baseline: the run fails, before anything is recorded, if
+//! an instruction uses more than `CU_REGRESSION_THRESHOLD` percent (5 by
+//! default) over it. `benches/savings.md` compares the latest tables.
+
+#[path = "../tests/mollusk/fixtures.rs"]
+mod fixtures;
+
+use std::fmt::Write;
+
+use fixtures::*;
 use mollusk_svm_bencher::MolluskComputeUnitBencher;
-use solana_pinocchio_starter::{
-    instruction::{InitializeMyStateIxData, UpdateMyStateIxData},
-    state::{to_bytes, DataLen, MyState, State},
-    ID,
-};
-use solana_sdk::pubkey;
 use solana_sdk::{
     account::Account,
-    instruction::{AccountMeta, Instruction},
-    native_token::LAMPORTS_PER_SOL,
+    instruction::Instruction,
     pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, LockupArgs},
+        state::{Authorized, Lockup, Meta, StakeAuthorize},
+    },
+    stake_history::StakeHistoryEntry,
 };
 
-pub const PROGRAM: Pubkey = Pubkey::new_from_array(ID);
+const RESULTS_DIR: &str = "benches/";
 
-pub const RENT: Pubkey = pubkey!("SysvarRent111111111111111111111111111111111");
+const NATIVE_RESULTS_DIR: &str = "benches/native/";
 
-pub const PAYER: Pubkey = pubkey!("41LzznNicELmc5iCR9Jxke62a3v1VhzpBYodQF5AQwHX");
+const SAVINGS: &str = "benches/savings.md";
 
-fn main() {
-    let mollusk = Mollusk::new(&PROGRAM, "target/deploy/solana_pinocchio_starter");
-
-    let (system_program, system_account) = program::keyed_account_for_system_program();
-
-    // Create the PDA
-    let (mystate_pda, bump) =
-        Pubkey::find_program_address(&[MyState::SEED.as_bytes(), &PAYER.to_bytes()], &PROGRAM);
-
-    //Initialize the accounts
-    let payer_account = Account::new(1 * LAMPORTS_PER_SOL, 0, &system_program);
-    let mystate_account = Account::new(0, 0, &system_program);
-    let min_balance = mollusk.sysvars.rent.minimum_balance(0);
-    let rent_account = Account::new(min_balance, 0, &RENT);
-
-    //Push the accounts in to the instruction_accounts vec!
-    let ix_accounts = vec![
-        AccountMeta::new(PAYER, true),
-        AccountMeta::new(mystate_pda, false),
-        AccountMeta::new_readonly(RENT, false),
-        AccountMeta::new_readonly(system_program, false),
-    ];
-
-    // Create the instruction data
-    let ix_data = InitializeMyStateIxData {
-        owner: *PAYER.as_array(),
-        data: [1; 32],
-        bump,
-    };
+/// default of `CU_REGRESSION_THRESHOLD`, in percent
+const DEFAULT_THRESHOLD: f64 = 5.0;
+
+/// every bench runs at this epoch
+const EPOCH: u64 = 20;
+
+/// the stake history has entries for this many epochs before `EPOCH`, with as
+/// much activating and deactivating stake as effective, so that warmup and
+/// cooldown through them take several epochs
+const HISTORY_EPOCHS: u64 = 10;
+
+const CLUSTER_STAKE: u64 = 1_000_000_000_000_000;
+
+const SEED: &str = "authority";
+
+const STAKE_AMOUNT: u64 = 1_000_000_000;
+
+struct Bench {
+    name: &'static str,
+    instruction: Instruction,
+    accounts: Vec<(Pubkey, Account)>,
+}
+
+struct Benches {
+    env: Env,
+    benches: Vec<Bench>,
+    stake: Pubkey,
+    other: Pubkey,
+    staker: Pubkey,
+    voter: Pubkey,
+    recipient: Pubkey,
+    custodian: Pubkey,
+    new_authority: Pubkey,
+}
+
+impl Benches {
+    fn new() -> Self {
+        Self {
+            env: env(Env::new()),
+            benches: Vec::new(),
+            stake: Pubkey::new_unique(),
+            other: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            custodian: Pubkey::new_unique(),
+            new_authority: Pubkey::new_unique(),
+        }
+    }
+
+    fn add(
+        &mut self,
+        name: &'static str,
+        instruction: Instruction,
+        accounts: &[(Pubkey, Account)],
+    ) {
+        let accounts = self.env.with_missing_accounts(&instruction, accounts);
+        self.benches.push(Bench {
+            name,
+            instruction,
+            accounts,
+        });
+    }
+
+    fn initialized(&self) -> Account {
+        initialized_account(meta(&self.staker), rent_exempt_reserve() + STAKE_AMOUNT)
+    }
+
+    fn locked_up(&self) -> Account {
+        let lockup = Lockup {
+            unix_timestamp: 1_000,
+            epoch: EPOCH + 1,
+            custodian: self.custodian,
+        };
+        initialized_account(
+            Meta {
+                lockup,
+                ..meta(&self.staker)
+            },
+            rent_exempt_reserve() + STAKE_AMOUNT,
+        )
+    }
+
+    fn delegated(&self, activation_epoch: u64, deactivation_epoch: u64) -> Account {
+        let mut stake = stake(&self.voter, STAKE_AMOUNT, activation_epoch);
+        stake.delegation.deactivation_epoch = deactivation_epoch;
+        delegated_account(
+            meta(&self.staker),
+            stake,
+            rent_exempt_reserve() + STAKE_AMOUNT,
+        )
+    }
+
+    fn active(&self) -> Account {
+        self.delegated(0, u64::MAX)
+    }
+
+    fn activating(&self) -> Account {
+        self.delegated(EPOCH, u64::MAX)
+    }
+
+    /// deactivated before the stake history starts, so fully inactive
+    fn deactivated(&self) -> Account {
+        self.delegated(0, EPOCH - HISTORY_EPOCHS - 1)
+    }
+
+    // these walk the stake history, where native computes in `f64`, which the
+    // SBF target has to emulate in software
+
+    fn warming_up(&self) -> Account {
+        self.delegated(EPOCH - HISTORY_EPOCHS / 2, u64::MAX)
+    }
+
+    fn cooling_down(&self) -> Account {
+        self.delegated(0, EPOCH - HISTORY_EPOCHS / 2)
+    }
+
+    fn initialize(&mut self) {
+        let authorized = Authorized::auto(&self.staker);
+        let accounts = [(self.stake, uninitialized_account(rent_exempt_reserve()))];
+
+        let instruction = ixn::initialize(&self.stake, &authorized, &Lockup::default());
+        self.add("Initialize", instruction, &accounts);
+        let instruction = ixn::initialize_checked(&self.stake, &authorized);
+        self.add("InitializeChecked", instruction, &accounts);
+    }
+
+    fn authorize(&mut self) {
+        let (stake, staker, new) = (self.stake, self.staker, self.new_authority);
+        let accounts = [(stake, self.initialized())];
+
+        let instruction = ixn::authorize(&stake, &staker, &new, StakeAuthorize::Staker, None);
+        self.add("Authorize", instruction, &accounts);
+
+        let instruction = ixn::authorize(
+            &stake,
+            &staker,
+            &new,
+            StakeAuthorize::Withdrawer,
+            Some(&self.custodian),
+        );
+        self.add(
+            "Authorize (withdrawer under lockup)",
+            instruction,
+            &[(stake, self.locked_up())],
+        );
 
-    // Ix discriminator = 0
-    let mut ser_ix_data = vec![0];
+        let instruction =
+            ixn::authorize_checked(&stake, &staker, &new, StakeAuthorize::Staker, None);
+        self.add("AuthorizeChecked", instruction, &accounts);
 
-    // Serialize the instruction data
-    ser_ix_data.extend_from_slice(unsafe { to_bytes(&ix_data) });
+        // staker and withdrawer derived from `base`
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let authority = Pubkey::create_with_seed(&base, SEED, &owner).unwrap();
+        let accounts = [(
+            stake,
+            initialized_account(meta(&authority), rent_exempt_reserve()),
+        )];
 
-    // Create instruction
-    let instruction0 = Instruction::new_with_bytes(PROGRAM, &ser_ix_data, ix_accounts);
+        let instruction = ixn::authorize_with_seed(
+            &stake,
+            &base,
+            SEED.to_string(),
+            &owner,
+            &new,
+            StakeAuthorize::Staker,
+            None,
+        );
+        self.add("AuthorizeWithSeed", instruction, &accounts);
+        let instruction = ixn::authorize_checked_with_seed(
+            &stake,
+            &base,
+            SEED.to_string(),
+            &owner,
+            &new,
+            StakeAuthorize::Staker,
+            None,
+        );
+        self.add("AuthorizeCheckedWithSeed", instruction, &accounts);
+    }
 
-    // Create tx_accounts vec
-    let tx_accounts0 = &vec![
-        (PAYER, payer_account.clone()),
-        (mystate_pda, mystate_account.clone()),
-        (RENT, rent_account.clone()),
-        (system_program, system_account.clone()),
-    ];
+    fn delegate(&mut self) {
+        let instruction = ixn::delegate_stake(&self.stake, &self.staker, &self.voter);
+        let vote = (self.voter, vote_account(&[]));
 
-    let rent = mollusk.sysvars.rent.minimum_balance(MyState::LEN);
-    let mut mystate_account = Account::new(rent, MyState::LEN, &ID.into());
+        self.add(
+            "DelegateStake (initialized)",
+            instruction.clone(),
+            &[(self.stake, self.initialized()), vote.clone()],
+        );
+        self.add(
+            "DelegateStake (deactivated)",
+            instruction.clone(),
+            &[(self.stake, self.deactivated()), vote.clone()],
+        );
+        // back to the same vote account in the epoch it was deactivated, which
+        // cancels the deactivation
+        let deactivating = self.delegated(EPOCH - HISTORY_EPOCHS / 2, EPOCH);
+        self.add(
+            "DelegateStake (warming up, deactivating)",
+            instruction,
+            &[(self.stake, deactivating), vote],
+        );
+    }
 
-    let my_state = MyState {
-        is_initialized: true,
-        owner: *PAYER.as_array(),
-        state: State::Initialized,
-        data: [1; 32],
-        update_count: 0,
+    fn split(&mut self) {
+        let instruction = ixn::split(&self.stake, &self.staker, STAKE_AMOUNT / 2, &self.other)
+            .pop()
+            .unwrap();
+
+        self.add(
+            "Split (initialized)",
+            instruction.clone(),
+            &[
+                (self.stake, self.initialized()),
+                (self.other, uninitialized_account(0)),
+            ],
+        );
+        self.add(
+            "Split (active)",
+            instruction.clone(),
+            &[
+                (self.stake, self.active()),
+                (self.other, uninitialized_account(rent_exempt_reserve())),
+            ],
+        );
+        self.add(
+            "Split (warming up)",
+            instruction,
+            &[
+                (self.stake, self.warming_up()),
+                (self.other, uninitialized_account(rent_exempt_reserve())),
+            ],
+        );
+    }
+
+    fn deactivate(&mut self) {
+        let instruction = ixn::deactivate_stake(&self.stake, &self.staker);
+        self.add("Deactivate", instruction, &[(self.stake, self.active())]);
+
+        // the reference voted in each of the last five epochs
+        let reference = Pubkey::new_unique();
+        let credits: Vec<_> = (EPOCH - 4..=EPOCH)
+            .map(|epoch| (epoch, epoch, epoch - 1))
+            .collect();
+        let instruction = ixn::deactivate_delinquent_stake(&self.stake, &self.voter, &reference);
+        self.add(
+            "DeactivateDelinquent",
+            instruction,
+            &[
+                (self.stake, self.active()),
+                (self.voter, vote_account(&[])),
+                (reference, vote_account(&credits)),
+            ],
+        );
+    }
+
+    fn merge(&mut self) {
+        let instruction = ixn::merge(&self.stake, &self.other, &self.staker)
+            .pop()
+            .unwrap();
+
+        let pairs = [
+            (
+                "Merge (inactive, inactive)",
+                self.initialized(),
+                self.deactivated(),
+            ),
+            (
+                "Merge (inactive, activating)",
+                self.initialized(),
+                self.activating(),
+            ),
+            (
+                "Merge (activating, inactive)",
+                self.activating(),
+                self.initialized(),
+            ),
+            (
+                "Merge (activating, activating)",
+                self.activating(),
+                self.activating(),
+            ),
+            ("Merge (active, active)", self.active(), self.active()),
+        ];
+        for (name, destination, source) in pairs {
+            self.add(
+                name,
+                instruction.clone(),
+                &[(self.stake, destination), (self.other, source)],
+            );
+        }
+    }
+
+    fn withdraw(&mut self) {
+        let instruction = ixn::withdraw(&self.stake, &self.staker, &self.recipient, 100, None);
+        self.add(
+            "Withdraw (initialized)",
+            instruction,
+            &[(self.stake, self.initialized())],
+        );
+
+        let lamports = rent_exempt_reserve() + STAKE_AMOUNT;
+        let instruction = ixn::withdraw(&self.stake, &self.staker, &self.recipient, lamports, None);
+        self.add(
+            "Withdraw (all of deactivated)",
+            instruction,
+            &[(self.stake, self.deactivated())],
+        );
+
+        // only the lamports over the stake and the reserve
+        let mut cooling_down = self.cooling_down();
+        cooling_down.lamports += 100;
+        let instruction = ixn::withdraw(&self.stake, &self.staker, &self.recipient, 100, None);
+        self.add(
+            "Withdraw (cooling down)",
+            instruction,
+            &[(self.stake, cooling_down)],
+        );
+
+        let instruction = ixn::withdraw(
+            &self.stake,
+            &self.staker,
+            &self.recipient,
+            100,
+            Some(&self.custodian),
+        );
+        self.add(
+            "Withdraw (lockup, custodian)",
+            instruction,
+            &[(self.stake, self.locked_up())],
+        );
+    }
+
+    fn set_lockup(&mut self) {
+        let args = LockupArgs {
+            unix_timestamp: Some(1_000),
+            epoch: Some(EPOCH + 1),
+            custodian: Some(self.custodian),
+        };
+        let accounts = [(self.stake, self.initialized())];
+
+        let instruction = ixn::set_lockup(&self.stake, &args, &self.staker);
+        self.add("SetLockup", instruction, &accounts);
+
+        // the new custodian signs instead of being passed in the data
+        let instruction = ixn::set_lockup_checked(&self.stake, &args, &self.staker);
+        self.add("SetLockupChecked", instruction, &accounts);
+    }
+
+    fn move_stake_and_lamports(&mut self) {
+        let (stake, other, staker) = (self.stake, self.other, self.staker);
+
+        let instruction = ixn::move_stake(&stake, &other, &staker, STAKE_AMOUNT / 4);
+        self.add(
+            "MoveStake (to active)",
+            instruction,
+            &[(stake, self.active()), (other, self.active())],
+        );
+        let instruction = ixn::move_stake(&stake, &other, &staker, STAKE_AMOUNT);
+        self.add(
+            "MoveStake (all, to inactive)",
+            instruction,
+            &[
+                (stake, self.active()),
+                (
+                    other,
+                    initialized_account(meta(&staker), rent_exempt_reserve()),
+                ),
+            ],
+        );
+
+        // only lamports over the stake and the reserve can move
+        let mut source = self.active();
+        source.lamports += 100;
+        let instruction = ixn::move_lamports(&stake, &other, &staker, 100);
+        self.add(
+            "MoveLamports",
+            instruction,
+            &[(stake, source), (other, self.active())],
+        );
+    }
+
+    /// `Redelegate` is disabled, so it isn't benched
+    fn all() -> Vec<Bench> {
+        let mut benches = Self::new();
+        benches.initialize();
+        benches.authorize();
+        benches.delegate();
+        benches.split();
+        benches.deactivate();
+        benches.merge();
+        benches.withdraw();
+        benches.set_lockup();
+        benches.move_stake_and_lamports();
+        benches.add("GetMinimumDelegation", ixn::get_minimum_delegation(), &[]);
+        benches.benches
+    }
+}
+
+/// `env` with the sysvars every bench runs with
+fn env(mut env: Env) -> Env {
+    env.mollusk.sysvars.clock.epoch = EPOCH;
+    for epoch in EPOCH - HISTORY_EPOCHS..EPOCH {
+        let entry = StakeHistoryEntry {
+            effective: CLUSTER_STAKE,
+            activating: CLUSTER_STAKE,
+            deactivating: CLUSTER_STAKE,
+        };
+        env.mollusk.sysvars.stake_history.add(epoch, entry);
+    }
+    env
+}
+
+/// The most recent table the bencher wrote to `dir`, read back the way the
+/// bencher reads it
+fn last_results(dir: &str) -> Vec<(String, u64)> {
+    let Ok(content) = std::fs::read_to_string(format!("{dir}compute_units.md")) else {
+        return Vec::new();
     };
 
-    mystate_account.data = unsafe { to_bytes(&my_state).to_vec() };
-
-    //Push the accounts in to the instruction_accounts vec!
-    let ix_accounts = vec![
-        AccountMeta::new(PAYER, true),
-        AccountMeta::new(mystate_pda, false),
-    ];
-
-    // Create the instruction data
-    let ix_data = UpdateMyStateIxData { data: [1; 32] };
-
-    // Ix discriminator = 1
-    let mut ser_ix_data = vec![1];
-
-    // Serialize the instruction data
-    ser_ix_data.extend_from_slice(unsafe { to_bytes(&ix_data) });
-
-    // Create instruction
-    let instruction1 = Instruction::new_with_bytes(PROGRAM, &ser_ix_data, ix_accounts);
-    // Create tx_accounts vec
-    let tx_accounts1 = &vec![
-        (PAYER, payer_account.clone()),
-        (mystate_pda, mystate_account.clone()),
-    ];
-
-    MolluskComputeUnitBencher::new(mollusk)
-        .bench(("InitializeMyState", &instruction0, tx_accounts0))
-        .bench(("UpdateMyState", &instruction1, tx_accounts1))
-        .must_pass(true)
-        .out_dir("benches/")
-        .execute();
+    content
+        .lines()
+        // the date, the CLI version and the table header
+        .skip(6)
+        .take_while(|line| line.starts_with('|'))
+        .map(|line| {
+            let mut columns = line.split('|').skip(1).map(str::trim);
+            let name = columns.next().unwrap().to_string();
+            let cus = columns.next().unwrap().parse().unwrap();
+            (name, cus)
+        })
+        .collect()
+}
+
+/// Fails if a bench uses more than `threshold` percent over the baseline
+fn check_regressions(benches: &[Bench], env: &Env, threshold: f64) {
+    let baseline = last_results(RESULTS_DIR);
+    let failures: Vec<String> = benches
+        .iter()
+        .filter_map(|bench| {
+            let (_, previous) = baseline.iter().find(|(name, _)| name == bench.name)?;
+            let cus = env
+                .mollusk
+                .process_instruction(&bench.instruction, &bench.accounts)
+                .compute_units_consumed;
+            (cus as f64 > *previous as f64 * (1.0 + threshold / 100.0))
+                .then(|| format!("{}: {cus} CUs, {previous} before", bench.name))
+        })
+        .collect();
+
+    assert!(
+        failures.is_empty(),
+        "more than {threshold}% over {RESULTS_DIR}compute_units.md:\n{}",
+        failures.join("\n")
+    );
+}
+
+fn record_savings() {
+    let native = last_results(NATIVE_RESULTS_DIR);
+    let mut content = String::from(
+        "# Compute units\n\
+        \n\
+        The latest tables of `compute_units.md` and `native/compute_units.md`,\n\
+        savings how many fewer CUs this program uses than the native one.\n\
+        \n\
+        | Name | CUs | Native CUs | Savings |\n\
+        |------|-----|------------|---------|\n",
+    );
+    for (name, cus) in last_results(RESULTS_DIR) {
+        let Some((_, native_cus)) = native.iter().find(|(native_name, _)| *native_name == name)
+        else {
+            continue;
+        };
+        let savings = 100.0 - cus as f64 * 100.0 / *native_cus as f64;
+        writeln!(content, "| {name} | {cus} | {native_cus} | {savings:.0}% |").unwrap();
+    }
+    std::fs::write(SAVINGS, content).unwrap();
+}
+
+fn main() {
+    let threshold = std::env::var("CU_REGRESSION_THRESHOLD")
+        .map(|value| {
+            value
+                .parse()
+                .expect("CU_REGRESSION_THRESHOLD is not a number")
+        })
+        .unwrap_or(DEFAULT_THRESHOLD);
+    let benches = Benches::all();
+
+    // before the bencher records the new numbers over the baseline
+    check_regressions(&benches, &env(Env::new()), threshold);
+
+    for (env, dir) in [
+        (env(Env::new()), RESULTS_DIR),
+        (env(Env::reference()), NATIVE_RESULTS_DIR),
+    ] {
+        benches
+            .iter()
+            .fold(
+                MolluskComputeUnitBencher::new(env.mollusk),
+                |bencher, bench| bencher.bench((bench.name, &bench.instruction, &bench.accounts)),
+            )
+            .must_pass(true)
+            .out_dir(dir)
+            .execute();
+    }
+
+    record_savings();
 }
@@ -1,6 +1,10 @@
 #![allow(unexpected_cfgs)]
 
-use crate::instruction::{self, StakeInstruction};
+use crate::{
+    error::StakeError,
+    instruction::{self, StakeInstruction},
+    state::{EpochRewardsSysvar, StakeAuthorize},
+};
 use pinocchio::{
     account_info::AccountInfo, default_panic_handler, no_allocator, program_entrypoint,
     program_error::ProgramError, pubkey::Pubkey, ProgramResult,
@@ -33,122 +37,240 @@ fn process_instruction(
     //     .split_at_checked(4)
     //     .ok_or(ProgramError::InvalidInstructionData)?;
     
-    let instruction = StakeInstruction::try_from(&ix_disc[0])?;
-
-    // TODO: add check for epoch_rewards_active
-    // let epoch_rewards_active = EpochRewards::get()
-    //         .map(|epoch_rewards| epoch_rewards.active)
-    //         .unwrap_or(false);
-    // if epoch_rewards_active && !matches!(instruction, StakeInstruction::GetMinimumDelegation) {
-    //     return Err(StakeError::EpochRewardsActive.into());
-    // }
+    let instruction = StakeInstruction::try_from(ix_disc)?;
 
+    // every arm decodes its payload before calling `check_epoch_rewards`, so
+    // malformed data is reported as such even while rewards are distributed
     match instruction {
         StakeInstruction::Initialize => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Initialize");
 
-            todo!()
+            let ix_data = instruction::InitializeIxData::from_bytes(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_initialize(accounts, ix_data.authorized, ix_data.lockup)
         }
         StakeInstruction::Authorize => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Authorize");
 
-            todo!()
+            let (new_authority, authority_type) =
+                instruction::parse_authorize_args(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_authorize(accounts, &new_authority, authority_type)
         }
         StakeInstruction::DelegateStake => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: DelegateStake");
 
-            todo!()
+            check_epoch_rewards()?;
+
+            instruction::process_delegate(accounts)
         }
         StakeInstruction::Split => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Split");
 
-            todo!()
+            let lamports = instruction::parse_lamports(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_split(accounts, lamports)
         }
         StakeInstruction::Withdraw => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Withdraw");
 
-            todo!()
+            let lamports = instruction::parse_lamports(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_withdraw(accounts, lamports)
         }
         StakeInstruction::Deactivate => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Deactivate");
 
-            todo!()
+            check_epoch_rewards()?;
+
+            instruction::process_deactivate(accounts)
         }
         StakeInstruction::SetLockup => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: SetLockup");
 
-            todo!()
+            let lockup = instruction::parse_lockup_args(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_set_lockup(accounts, &lockup)
         }
         StakeInstruction::Merge => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: Merge");
 
-            todo!()
+            check_epoch_rewards()?;
+
+            instruction::process_merge(accounts)
         }
         StakeInstruction::AuthorizeWithSeed => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: AuthorizeWithSeed");
 
-            todo!()
+            let authorize_args = instruction::AuthorizeWithSeedArgs::from_bytes(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_authorize_with_seed(accounts, &authorize_args)
         }
         StakeInstruction::InitializeChecked => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: InitializeChecked");
 
-            todo!()
+            check_epoch_rewards()?;
+
+            instruction::process_initialize_checked(accounts)
         }
         StakeInstruction::AuthorizeChecked => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: AuthorizeChecked");
 
-            todo!()
+            let authority_type = StakeAuthorize::from_bytes(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_authorize_checked(accounts, authority_type)
         }
         StakeInstruction::AuthorizeCheckedWithSeed => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: AuthorizeCheckedWithSeed");
 
-            todo!()
+            let authorize_args =
+                instruction::AuthorizeCheckedWithSeedArgs::from_bytes(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_authorize_checked_with_seed(accounts, &authorize_args)
         }
         StakeInstruction::SetLockupChecked => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: SetLockupChecked");
 
-            todo!()
+            let lockup_checked = instruction::parse_lockup_checked_args(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_set_lockup_checked(accounts, &lockup_checked)
         }
         StakeInstruction::GetMinimumDelegation => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: GetMinimumDelegation");
 
-            todo!()
+            instruction::process_get_minimum_delegation()
         }
         StakeInstruction::DeactivateDelinquent => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: DeactivateDelinquent");
 
-            todo!()
+            check_epoch_rewards()?;
+
+            instruction::process_deactivate_delinquent(accounts)
         }
         #[allow(deprecated)]
-        StakeInstruction::Redelegate => Err(ProgramError::InvalidInstructionData),
+        StakeInstruction::Redelegate => {
+            check_epoch_rewards()?;
+
+            Err(ProgramError::InvalidInstructionData)
+        }
         // NOTE we assume the program is going live after `move_stake_and_move_lamports_ixs` is
         // activated
         StakeInstruction::MoveStake => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: MoveStake");
 
-            todo!()
+            let lamports = instruction::parse_lamports(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_move_stake(accounts, lamports)
         }
         StakeInstruction::MoveLamports => {
             #[cfg(feature = "logging")]
             pinocchio::msg!("Instruction: MoveLamports");
 
-            todo!()
+            let lamports = instruction::parse_lamports(instruction_data)?;
+
+            check_epoch_rewards()?;
+
+            instruction::process_move_lamports(accounts, lamports)
         }
     }
-}
\ No newline at end of file
+}
+
+/// Stake accounts must not change while epoch rewards are being distributed.
+#[inline(always)]
+fn check_epoch_rewards() -> ProgramResult {
+    if EpochRewardsSysvar::is_active() {
+        return Err(StakeError::EpochRewardsActive.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::{epoch_rewards::EpochRewards, sysvar};
+
+    use super::*;
+    use crate::state::{with_syscall_stubs, SysvarStubs};
+
+    fn with_epoch_rewards_active<R>(f: impl FnOnce() -> R) -> R {
+        let epoch_rewards = EpochRewards {
+            active: true,
+            ..EpochRewards::default()
+        };
+        let stubs = SysvarStubs::default()
+            .with_sysvar(&sysvar::epoch_rewards::ID.to_bytes(), &epoch_rewards);
+        with_syscall_stubs(stubs, f)
+    }
+
+    fn process(instruction: StakeInstruction) -> ProgramResult {
+        process_instruction(&crate::ID, &[], &(instruction as u32).to_le_bytes())
+    }
+
+    #[test]
+    fn test_check_epoch_rewards_active() {
+        with_epoch_rewards_active(|| {
+            assert_eq!(
+                check_epoch_rewards(),
+                Err(StakeError::EpochRewardsActive.into())
+            );
+            // before the accounts are even looked at
+            assert_eq!(
+                process(StakeInstruction::Deactivate),
+                Err(StakeError::EpochRewardsActive.into())
+            );
+        });
+    }
+
+    #[test]
+    fn test_check_epoch_rewards_get_minimum_delegation() {
+        with_epoch_rewards_active(|| {
+            assert_eq!(process(StakeInstruction::GetMinimumDelegation), Ok(()));
+        });
+    }
+
+    #[test]
+    fn test_check_epoch_rewards_unavailable() {
+        with_syscall_stubs(SysvarStubs::default(), || {
+            assert_eq!(check_epoch_rewards(), Ok(()));
+            assert_eq!(
+                process(StakeInstruction::Deactivate),
+                Err(ProgramError::NotEnoughAccountKeys)
+            );
+        });
+    }
+}
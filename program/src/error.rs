@@ -1,5 +1,3 @@
-use core::fmt;
-
 use pinocchio::program_error::ProgramError;
 
 pub trait FromPrimitive {
@@ -0,0 +1,57 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    consts::MAX_SIGNERS,
+    state::{
+        clock_from_account_info, collect_signers, do_authorize, next_account_info, StakeAuthorize,
+    },
+};
+
+/// Decodes the bincode `(Pubkey, StakeAuthorize)` arguments of `Authorize`
+/// following the discriminator.
+pub fn parse_authorize_args(data: &[u8]) -> Result<(Pubkey, StakeAuthorize), ProgramError> {
+    let (new_authority, rest) = data
+        .split_first_chunk::<32>()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok((*new_authority, StakeAuthorize::from_bytes(rest)?))
+}
+
+pub fn process_authorize(
+    accounts: &[AccountInfo],
+    new_authority: &Pubkey,
+    authority_type: StakeAuthorize,
+) -> ProgramResult {
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 3 accounts (1 sysvar)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _stake_or_withdraw_authority_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    let option_lockup_authority_info = next_account_info(account_info_iter).ok();
+
+    let clock = *clock_from_account_info(clock_info)?;
+
+    let custodian = option_lockup_authority_info
+        .filter(|a| a.is_signer())
+        .map(|a| a.key());
+
+    // `get_stake_state()` is called unconditionally, which checks owner
+    do_authorize(
+        stake_account_info,
+        signers,
+        new_authority,
+        authority_type,
+        custodian,
+        &clock,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    instruction::parse_seed_and_owner,
+    state::{
+        clock_from_account_info, create_with_seed, do_authorize, next_account_info, StakeAuthorize,
+    },
+};
+
+/// Arguments of `AuthorizeCheckedWithSeed`, borrowing the seed from the
+/// instruction data. The new authority is taken from a signing account.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AuthorizeCheckedWithSeedArgs<'a> {
+    pub stake_authorize: StakeAuthorize,
+    pub authority_seed: &'a [u8],
+    pub authority_owner: Pubkey,
+}
+
+impl<'a> AuthorizeCheckedWithSeedArgs<'a> {
+    /// Decodes the bincode `AuthorizeCheckedWithSeedArgs` following the
+    /// discriminator.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let stake_authorize = StakeAuthorize::from_bytes(data)?;
+        let (authority_seed, authority_owner) = parse_seed_and_owner(&data[StakeAuthorize::LEN..])?;
+
+        Ok(Self {
+            stake_authorize,
+            authority_seed,
+            authority_owner,
+        })
+    }
+}
+
+pub fn process_authorize_checked_with_seed(
+    accounts: &[AccountInfo],
+    authorize_args: &AuthorizeCheckedWithSeedArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 4 accounts (1 sysvar)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let old_stake_or_withdraw_authority_base_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let new_stake_or_withdraw_authority_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    let option_lockup_authority_info = next_account_info(account_info_iter).ok();
+
+    let clock = *clock_from_account_info(clock_info)?;
+
+    if !new_stake_or_withdraw_authority_info.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // unlike `Authorize`, a custodian that is passed must have signed
+    let custodian = match option_lockup_authority_info {
+        Some(custodian_info) if custodian_info.is_signer() => Some(custodian_info.key()),
+        Some(_) => return Err(ProgramError::MissingRequiredSignature),
+        None => None,
+    };
+
+    // like native, the signers are the new authority, the custodian, if any,
+    // and the derived authority, when its base signed. The base itself is not
+    // one.
+    let mut signers_arr = [Pubkey::default(); 3];
+    signers_arr[0] = *new_stake_or_withdraw_authority_info.key();
+    let mut signers_len = 1;
+    if let Some(custodian) = custodian {
+        signers_arr[signers_len] = *custodian;
+        signers_len += 1;
+    }
+    if old_stake_or_withdraw_authority_base_info.is_signer() {
+        signers_arr[signers_len] = create_with_seed(
+            old_stake_or_withdraw_authority_base_info.key(),
+            authorize_args.authority_seed,
+            &authorize_args.authority_owner,
+        )?;
+        signers_len += 1;
+    }
+    let signers = &signers_arr[..signers_len];
+
+    // `get_stake_state()` is called unconditionally, which checks owner
+    do_authorize(
+        stake_account_info,
+        signers,
+        new_stake_or_withdraw_authority_info.key(),
+        authorize_args.stake_authorize,
+        custodian,
+        &clock,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthorizeCheckedWithSeedArgs;
+    use crate::state::StakeAuthorize;
+    use pinocchio::program_error::ProgramError;
+    use solana_sdk::{
+        pubkey::Pubkey,
+        stake::{instruction, state},
+    };
+
+    #[test]
+    fn test_parse_authorize_checked_with_seed_args() {
+        let args = instruction::AuthorizeCheckedWithSeedArgs {
+            stake_authorize: state::StakeAuthorize::Staker,
+            authority_seed: "seed".to_string(),
+            authority_owner: Pubkey::new_unique(),
+        };
+        let data = bincode::serialize(&args).unwrap();
+
+        assert_eq!(
+            AuthorizeCheckedWithSeedArgs::from_bytes(&data),
+            Ok(AuthorizeCheckedWithSeedArgs {
+                stake_authorize: StakeAuthorize::Staker,
+                authority_seed: b"seed",
+                authority_owner: args.authority_owner.to_bytes(),
+            })
+        );
+
+        for len in 0..data.len() {
+            assert_eq!(
+                AuthorizeCheckedWithSeedArgs::from_bytes(&data[..len]),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::state::{
+    clock_from_account_info, create_with_seed, do_authorize, next_account_info, StakeAuthorize,
+};
+
+/// Arguments of `AuthorizeWithSeed`, borrowing the seed from the instruction
+/// data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AuthorizeWithSeedArgs<'a> {
+    pub new_authorized_pubkey: Pubkey,
+    pub stake_authorize: StakeAuthorize,
+    pub authority_seed: &'a [u8],
+    pub authority_owner: Pubkey,
+}
+
+impl<'a> AuthorizeWithSeedArgs<'a> {
+    /// Decodes the bincode `AuthorizeWithSeedArgs` following the discriminator.
+    ///
+    /// Trailing bytes are ignored, as bincode does for the native program.
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let (new_authorized_pubkey, data) = data
+            .split_first_chunk::<32>()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let stake_authorize = StakeAuthorize::from_bytes(data)?;
+        let (authority_seed, authority_owner) = parse_seed_and_owner(&data[StakeAuthorize::LEN..])?;
+
+        Ok(Self {
+            new_authorized_pubkey: *new_authorized_pubkey,
+            stake_authorize,
+            authority_seed,
+            authority_owner,
+        })
+    }
+}
+
+/// Decodes the bincode `(String, Pubkey)` seed and owner shared by the
+/// `*WithSeed` arguments. The seed must be valid UTF-8, like a `String`.
+pub(crate) fn parse_seed_and_owner(data: &[u8]) -> Result<(&[u8], Pubkey), ProgramError> {
+    let (seed_len, data) = data
+        .split_first_chunk::<8>()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let seed_len = usize::try_from(u64::from_le_bytes(*seed_len))
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    if data.len() < seed_len {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (seed, data) = data.split_at(seed_len);
+    core::str::from_utf8(seed).map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    let owner = data
+        .first_chunk::<32>()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok((seed, *owner))
+}
+
+pub fn process_authorize_with_seed(
+    accounts: &[AccountInfo],
+    authorize_args: &AuthorizeWithSeedArgs,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 2 accounts (1 sysvar)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let stake_or_withdraw_authority_base_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    let option_lockup_authority_info = next_account_info(account_info_iter).ok();
+
+    let clock = *clock_from_account_info(clock_info)?;
+
+    // unlike `Authorize`, a custodian that is passed must have signed
+    let custodian = match option_lockup_authority_info {
+        Some(custodian_info) if custodian_info.is_signer() => Some(custodian_info.key()),
+        Some(_) => return Err(ProgramError::MissingRequiredSignature),
+        None => None,
+    };
+
+    // like native, the signers are the custodian, if any, and the
+    // derived authority, when its base signed. The base itself is not one.
+    let mut signers_arr = [Pubkey::default(); 2];
+    let mut signers_len = 0;
+    if let Some(custodian) = custodian {
+        signers_arr[signers_len] = *custodian;
+        signers_len += 1;
+    }
+    if stake_or_withdraw_authority_base_info.is_signer() {
+        signers_arr[signers_len] = create_with_seed(
+            stake_or_withdraw_authority_base_info.key(),
+            authorize_args.authority_seed,
+            &authorize_args.authority_owner,
+        )?;
+        signers_len += 1;
+    }
+    let signers = &signers_arr[..signers_len];
+
+    // `get_stake_state()` is called unconditionally, which checks owner
+    do_authorize(
+        stake_account_info,
+        signers,
+        &authorize_args.new_authorized_pubkey,
+        authorize_args.stake_authorize,
+        custodian,
+        &clock,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AuthorizeWithSeedArgs;
+    use crate::state::{create_with_seed, StakeAuthorize};
+    use pinocchio::program_error::ProgramError;
+    use solana_sdk::{
+        pubkey::Pubkey,
+        stake::{instruction, state},
+    };
+
+    #[test]
+    fn test_parse_authorize_with_seed_args() {
+        let args = instruction::AuthorizeWithSeedArgs {
+            new_authorized_pubkey: Pubkey::new_unique(),
+            stake_authorize: state::StakeAuthorize::Withdrawer,
+            authority_seed: "seed ☃".to_string(),
+            authority_owner: Pubkey::new_unique(),
+        };
+        let mut data = bincode::serialize(&args).unwrap();
+
+        let expected = AuthorizeWithSeedArgs {
+            new_authorized_pubkey: args.new_authorized_pubkey.to_bytes(),
+            stake_authorize: StakeAuthorize::Withdrawer,
+            authority_seed: args.authority_seed.as_bytes(),
+            authority_owner: args.authority_owner.to_bytes(),
+        };
+        assert_eq!(AuthorizeWithSeedArgs::from_bytes(&data), Ok(expected));
+
+        for len in 0..data.len() {
+            assert_eq!(
+                AuthorizeWithSeedArgs::from_bytes(&data[..len]),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+
+        // invalid StakeAuthorize
+        data[32] = 2;
+        assert_eq!(
+            AuthorizeWithSeedArgs::from_bytes(&data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+
+        // invalid UTF-8 seed
+        data[32] = 0;
+        data[32 + 4 + 8] = 0xff;
+        assert_eq!(
+            AuthorizeWithSeedArgs::from_bytes(&data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_create_with_seed() {
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+
+        for seed in ["", "stake", "☃", "0123456789abcdef0123456789abcdef"] {
+            assert_eq!(
+                create_with_seed(&base.to_bytes(), seed.as_bytes(), &owner.to_bytes()),
+                Ok(Pubkey::create_with_seed(&base, seed, &owner)
+                    .unwrap()
+                    .to_bytes())
+            );
+        }
+
+        assert_eq!(
+            create_with_seed(&base.to_bytes(), &[b'a'; 33], &owner.to_bytes()),
+            Err(ProgramError::MaxSeedLengthExceeded)
+        );
+
+        let mut pda_owner = [7; 32];
+        pda_owner[11..].copy_from_slice(b"ProgramDerivedAddress");
+        assert_eq!(
+            create_with_seed(&base.to_bytes(), b"seed", &pda_owner),
+            Err(ProgramError::IllegalOwner)
+        );
+    }
+}
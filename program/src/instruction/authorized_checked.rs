@@ -2,16 +2,20 @@ use pinocchio::{
     account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
 };
 
-use crate::state::{
-    clock_from_account_info, collect_signers, do_authorize, next_account_info, StakeAuthorize,
+use crate::{
+    consts::MAX_SIGNERS,
+    state::{
+        clock_from_account_info, collect_signers, do_authorize, next_account_info, StakeAuthorize,
+    },
 };
 
 pub fn process_authorize_checked(
     accounts: &[AccountInfo],
     authority_type: StakeAuthorize,
 ) -> ProgramResult {
-    let mut signers = [Pubkey::default(); 32];
-    let _signers_len = collect_signers(accounts, &mut signers)?;
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
     let account_info_iter = &mut accounts.iter();
 
     // native asserts: 4 accounts (1 sysvar)
@@ -36,7 +40,7 @@ pub fn process_authorize_checked(
     // `get_stake_state()` is called unconditionally, which checks owner
     do_authorize(
         stake_account_info,
-        &signers,
+        signers,
         new_stake_or_withdraw_authority_info.key(),
         authority_type,
         custodian,
@@ -0,0 +1,45 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    consts::MAX_SIGNERS,
+    state::{
+        clock_from_account_info, collect_signers, deactivate_stake, get_stake_state,
+        next_account_info, set_stake_state, to_program_error, StakeAuthorize, StakeStateV2,
+    },
+};
+
+pub fn process_deactivate(accounts: &[AccountInfo]) -> ProgramResult {
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 2 accounts (1 sysvar)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    // let _stake_authority_info = next_account_info(account_info_iter);
+
+    let clock = *clock_from_account_info(clock_info)?;
+
+    match get_stake_state(stake_account_info)? {
+        StakeStateV2::Stake(meta, mut stake, mut stake_flags) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Staker)
+                .map_err(to_program_error)?;
+
+            deactivate_stake(&mut stake, &mut stake_flags, clock.epoch)?;
+
+            set_stake_state(
+                stake_account_info,
+                &StakeStateV2::Stake(meta, stake, stake_flags),
+            )
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }?;
+
+    Ok(())
+}
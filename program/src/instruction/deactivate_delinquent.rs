@@ -0,0 +1,158 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    consts::MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION,
+    error::StakeError,
+    state::{
+        deactivate_stake, get_stake_state, get_vote_state, next_account_info, set_stake_state,
+        StakeStateV2, VoteStateView,
+    },
+};
+
+pub fn process_deactivate_delinquent(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 3 accounts
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let delinquent_vote_account_info = next_account_info(account_info_iter)?;
+    let reference_vote_account_info = next_account_info(account_info_iter)?;
+
+    let clock = Clock::get()?;
+
+    let delinquent_vote_state = get_vote_state(delinquent_vote_account_info)?;
+    let reference_vote_state = get_vote_state(reference_vote_account_info)?;
+
+    if !acceptable_reference_epoch_credits(&reference_vote_state, clock.epoch) {
+        return Err(StakeError::InsufficientReferenceVotes.into());
+    }
+
+    if let StakeStateV2::Stake(meta, mut stake, mut stake_flags) =
+        get_stake_state(stake_account_info)?
+    {
+        if stake.delegation.voter_pubkey != *delinquent_vote_account_info.key() {
+            return Err(StakeError::VoteAddressMismatch.into());
+        }
+
+        // Deactivate the stake account if its delegated vote account has never voted or
+        // has not voted in the last MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION
+        if eligible_for_deactivate_delinquent(&delinquent_vote_state, clock.epoch) {
+            deactivate_stake(&mut stake, &mut stake_flags, clock.epoch)?;
+
+            set_stake_state(
+                stake_account_info,
+                &StakeStateV2::Stake(meta, stake, stake_flags),
+            )
+        } else {
+            Err(StakeError::MinimumDelinquentEpochsForDeactivationNotMet.into())
+        }
+    } else {
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+/// Check that the reference vote account voted in each of the last
+/// `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs, current epoch included
+pub(crate) fn acceptable_reference_epoch_credits(
+    vote_state: &VoteStateView,
+    current_epoch: u64,
+) -> bool {
+    if vote_state.epoch_credits_len() < MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION {
+        return false;
+    }
+
+    let mut epoch = current_epoch;
+    for (vote_epoch, ..) in vote_state
+        .epoch_credits()
+        .rev()
+        .take(MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION)
+    {
+        if vote_epoch != epoch {
+            return false;
+        }
+        epoch = epoch.saturating_sub(1);
+    }
+
+    true
+}
+
+/// Check that the vote account never voted, or did not vote in the last
+/// `MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION` epochs
+pub(crate) fn eligible_for_deactivate_delinquent(
+    vote_state: &VoteStateView,
+    current_epoch: u64,
+) -> bool {
+    match vote_state.epoch_credits().next_back() {
+        None => true,
+        Some((epoch, ..)) => current_epoch
+            .checked_sub(MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as u64)
+            .is_some_and(|minimum_epoch| epoch <= minimum_epoch),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{acceptable_reference_epoch_credits, eligible_for_deactivate_delinquent};
+    use crate::{consts::MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION, state::VoteStateView};
+    use solana_sdk::vote::state::{VoteState, VoteStateVersions};
+
+    const N: u64 = MINIMUM_DELINQUENT_EPOCHS_FOR_DEACTIVATION as u64;
+
+    fn serialize(epochs: &[u64]) -> Vec<u8> {
+        let vote_state = VoteState {
+            epoch_credits: epochs.iter().map(|epoch| (*epoch, 1, 0)).collect(),
+            ..VoteState::default()
+        };
+
+        let mut data = vec![0; VoteState::size_of()];
+        bincode::serialize_into(&mut data[..], &VoteStateVersions::new_current(vote_state))
+            .unwrap();
+        data
+    }
+
+    fn acceptable(epochs: &[u64], current_epoch: u64) -> bool {
+        let data = serialize(epochs);
+        acceptable_reference_epoch_credits(
+            &VoteStateView::from_bytes(&data).unwrap(),
+            current_epoch,
+        )
+    }
+
+    fn eligible(epochs: &[u64], current_epoch: u64) -> bool {
+        let data = serialize(epochs);
+        eligible_for_deactivate_delinquent(
+            &VoteStateView::from_bytes(&data).unwrap(),
+            current_epoch,
+        )
+    }
+
+    #[test]
+    fn test_acceptable_reference_epoch_credits() {
+        assert!(!acceptable(&[], 0));
+        assert!(!acceptable(&[0, 1, 2, 3], 3));
+        assert!(acceptable(&[0, 1, 2, 3, 4], 4));
+        assert!(acceptable(&[0, 1, 2, 3, 4, 5], 5));
+        assert!(acceptable(&[0, 1, 5, 6, 7, 8, 9], 9));
+        // missed an epoch
+        assert!(!acceptable(&[0, 1, 2, 3, 5, 6], 6));
+        // did not vote in the current epoch
+        assert!(!acceptable(&[0, 1, 2, 3, 4], 5));
+    }
+
+    #[test]
+    fn test_eligible_for_deactivate_delinquent() {
+        // never voted
+        assert!(eligible(&[], 0));
+
+        assert!(!eligible(&[0], N - 1));
+        assert!(eligible(&[0], N));
+        assert!(!eligible(&[1], N));
+        assert!(eligible(&[0, 1, 2], N + 2));
+        assert!(!eligible(&[0, 1, 2, 3], N + 2));
+        assert!(!eligible(&[0, 1, 2, 3], 0));
+    }
+}
@@ -0,0 +1,136 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    consts::{MAX_SIGNERS, PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH},
+    error::StakeError,
+    state::{
+        clock_from_account_info, collect_signers, get_stake_state, get_vote_state,
+        next_account_info, set_stake_state, to_program_error, validate_delegated_amount,
+        Delegation, Epoch, Stake, StakeAuthorize, StakeFlags, StakeHistoryGetEntry,
+        StakeHistorySysvar, StakeStateV2, ValidatedDelegatedInfo, VoteStateView,
+    },
+};
+
+pub fn process_delegate(accounts: &[AccountInfo]) -> ProgramResult {
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 4 accounts (2 sysvars)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let vote_account_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _stake_history_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    // let _stake_config_info = next_account_info(account_info_iter);
+    // let _stake_authority_info = next_account_info(account_info_iter);
+
+    let clock = *clock_from_account_info(clock_info)?;
+    let stake_history = &StakeHistorySysvar(clock.epoch);
+
+    let vote_state = get_vote_state(vote_account_info)?;
+
+    match get_stake_state(stake_account_info)? {
+        StakeStateV2::Initialized(meta) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Staker)
+                .map_err(to_program_error)?;
+
+            let ValidatedDelegatedInfo { stake_amount } =
+                validate_delegated_amount(stake_account_info, &meta)?;
+
+            let stake = new_stake(
+                stake_amount,
+                vote_account_info.key(),
+                &vote_state,
+                clock.epoch.into(),
+            );
+
+            set_stake_state(
+                stake_account_info,
+                &StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+            )
+        }
+        StakeStateV2::Stake(meta, mut stake, stake_flags) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Staker)
+                .map_err(to_program_error)?;
+
+            let ValidatedDelegatedInfo { stake_amount } =
+                validate_delegated_amount(stake_account_info, &meta)?;
+
+            redelegate_stake(
+                &mut stake,
+                stake_amount,
+                vote_account_info.key(),
+                &vote_state,
+                clock.epoch.into(),
+                stake_history,
+            )?;
+
+            set_stake_state(
+                stake_account_info,
+                &StakeStateV2::Stake(meta, stake, stake_flags),
+            )
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }?;
+
+    Ok(())
+}
+
+fn new_stake(
+    stake: u64,
+    voter_pubkey: &Pubkey,
+    vote_state: &VoteStateView,
+    activation_epoch: Epoch,
+) -> Stake {
+    let mut new_stake = Stake::default();
+    new_stake.delegation = Delegation::new(voter_pubkey, stake, activation_epoch);
+    new_stake.set_credits_observed(vote_state.credits());
+    new_stake
+}
+
+fn redelegate_stake<T: StakeHistoryGetEntry>(
+    stake: &mut Stake,
+    stake_lamports: u64,
+    voter_pubkey: &Pubkey,
+    vote_state: &VoteStateView,
+    epoch: Epoch,
+    stake_history: &T,
+) -> ProgramResult {
+    // If stake is currently active:
+    if stake.stake(
+        epoch,
+        stake_history,
+        PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+    ) != 0
+    {
+        // If pubkey of new voter is the same as current,
+        // and we are scheduled to start deactivating this epoch,
+        // we rescind deactivation
+        if stake.delegation.voter_pubkey == *voter_pubkey
+            && epoch == stake.delegation.deactivation_epoch
+        {
+            stake.delegation.set_deactivation_epoch(u64::MAX);
+            return Ok(());
+        } else {
+            // can't redelegate to another pubkey if stake is active.
+            return Err(StakeError::TooSoonToRedelegate.into());
+        }
+    }
+    // Either the stake is freshly activated, is active but has been
+    // deactivated this epoch, or has fallen into any other state
+    // (e.g. stake is fully deactivated), so we can simply redelegate.
+
+    stake.delegation.set_stake(stake_lamports);
+    stake.delegation.activation_epoch = epoch;
+    stake.delegation.set_deactivation_epoch(u64::MAX);
+    stake.delegation.voter_pubkey = *voter_pubkey;
+    stake.set_credits_observed(vote_state.credits());
+    Ok(())
+}
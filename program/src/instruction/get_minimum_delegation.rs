@@ -0,0 +1,12 @@
+use pinocchio::{cpi::set_return_data, ProgramResult};
+
+use crate::state::get_minimum_delegation;
+
+/// Publishes the minimum delegation as little-endian `u64` return data, for CPI
+/// callers and simulations to read
+pub fn process_get_minimum_delegation() -> ProgramResult {
+    let minimum_delegation = get_minimum_delegation();
+    set_return_data(&minimum_delegation.to_le_bytes());
+
+    Ok(())
+}
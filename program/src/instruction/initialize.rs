@@ -0,0 +1,143 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, sysvars::rent::Rent, ProgramResult,
+};
+
+use crate::state::{
+    do_initialize, next_account_info,
+    utils::{load_ix_data, DataLen},
+    Authorized, Lockup,
+};
+
+/// Instruction data of `StakeInstruction::Initialize`, laid out exactly like the
+/// bincode encoding of `(Authorized, Lockup)`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InitializeIxData {
+    pub authorized: Authorized,
+    pub lockup: Lockup,
+}
+
+impl DataLen for InitializeIxData {
+    const LEN: usize = core::mem::size_of::<InitializeIxData>();
+}
+
+impl InitializeIxData {
+    /// Reads the arguments from the bytes following the discriminator.
+    ///
+    /// Trailing bytes are ignored, as bincode does for the native program.
+    pub fn from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
+        let data = data
+            .get(..Self::LEN)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        unsafe { load_ix_data::<Self>(data) }
+    }
+}
+
+pub fn process_initialize(
+    accounts: &[AccountInfo],
+    authorized: Authorized,
+    lockup: Lockup,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 2 accounts (1 sysvar)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    let rent = &*Rent::from_account_info(rent_info)?;
+
+    // `get_stake_state()` is called unconditionally, which checks owner
+    do_initialize(stake_account_info, authorized, lockup, rent)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::MaybeUninit;
+
+    use pinocchio::{account_info::MAX_PERMITTED_DATA_INCREASE, entrypoint::deserialize};
+    use solana_sdk::{
+        pubkey::Pubkey as NativePubkey,
+        rent::Rent as NativeRent,
+        stake::state::{
+            Authorized as NativeAuthorized, Lockup as NativeLockup, Meta as NativeMeta,
+            StakeStateV2 as NativeStakeStateV2,
+        },
+    };
+
+    use super::*;
+    use crate::state::StakeStateV2;
+
+    /// The program input the runtime serializes for a single writable account
+    /// owned by this program, for `deserialize` to build its `AccountInfo`.
+    /// It's held in `u64`s, aligned like the runtime's buffer.
+    fn program_input(lamports: u64, data: &[u8]) -> Vec<u64> {
+        let mut input = Vec::new();
+        input.extend_from_slice(&1u64.to_le_bytes());
+        // not a duplicate, not a signer, writable, not executable, padding
+        input.extend_from_slice(&[u8::MAX, 0, 1, 0, 0, 0, 0, 0]);
+        input.extend_from_slice(&[0; 32]);
+        input.extend_from_slice(&crate::ID);
+        input.extend_from_slice(&lamports.to_le_bytes());
+        input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+        input.extend_from_slice(data);
+        input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        input.resize(input.len().next_multiple_of(8), 0);
+        // rent epoch, then no instruction data
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.extend_from_slice(&crate::ID);
+
+        input
+            .chunks(8)
+            .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_initialize_matches_bincode() {
+        let authorized = NativeAuthorized {
+            staker: NativePubkey::new_unique(),
+            withdrawer: NativePubkey::new_unique(),
+        };
+        let lockup = NativeLockup {
+            unix_timestamp: -1,
+            epoch: 2,
+            custodian: NativePubkey::new_unique(),
+        };
+        let native_rent = NativeRent::default();
+        let rent = Rent {
+            lamports_per_byte_year: native_rent.lamports_per_byte_year,
+            exemption_threshold: native_rent.exemption_threshold,
+            burn_percent: native_rent.burn_percent,
+        };
+        let rent_exempt_reserve = native_rent.minimum_balance(StakeStateV2::size_of());
+
+        let mut input = program_input(rent_exempt_reserve, &[0; StakeStateV2::size_of()]);
+        let mut accounts = [MaybeUninit::<AccountInfo>::uninit()];
+        unsafe { deserialize::<1>(input.as_mut_ptr() as *mut u8, &mut accounts) };
+        let stake_account_info = unsafe { accounts[0].assume_init_ref() };
+
+        let ix_data = bincode::serialize(&(authorized, lockup)).unwrap();
+        let ix_data = InitializeIxData::from_bytes(&ix_data).unwrap();
+        do_initialize(
+            stake_account_info,
+            ix_data.authorized,
+            ix_data.lockup,
+            &rent,
+        )
+        .unwrap();
+
+        // every one of the 200 bytes, including the zeroed tail
+        let mut expected = vec![0; StakeStateV2::size_of()];
+        let state = NativeStakeStateV2::Initialized(NativeMeta {
+            rent_exempt_reserve,
+            authorized,
+            lockup,
+        });
+        bincode::serialize_into(&mut expected[..], &state).unwrap();
+        assert_eq!(*stake_account_info.try_borrow_data().unwrap(), expected[..]);
+    }
+}
@@ -0,0 +1,31 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, sysvars::rent::Rent, ProgramResult,
+};
+
+use crate::state::{do_initialize, next_account_info, Authorized, Lockup};
+
+pub fn process_initialize_checked(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 4 accounts (1 sysvar)
+    let stake_account_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let stake_authority_info = next_account_info(account_info_iter)?;
+    let withdraw_authority_info = next_account_info(account_info_iter)?;
+
+    let rent = &*Rent::from_account_info(rent_info)?;
+
+    if !withdraw_authority_info.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let authorized = Authorized {
+        staker: *stake_authority_info.key(),
+        withdrawer: *withdraw_authority_info.key(),
+    };
+
+    // `get_stake_state()` is called unconditionally, which checks owner
+    do_initialize(stake_account_info, authorized, Lockup::default(), rent)?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+use crate::{
+    consts::MAX_SIGNERS,
+    state::{
+        clock_from_account_info, collect_signers, get_stake_state, next_account_info,
+        relocate_lamports, set_stake_state, MergeKind, StakeAuthorize, StakeHistorySysvar,
+        StakeStateV2,
+    },
+};
+
+pub fn process_merge(accounts: &[AccountInfo]) -> ProgramResult {
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 4 accounts (2 sysvars)
+    let destination_stake_account_info = next_account_info(account_info_iter)?;
+    let source_stake_account_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _stake_history_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    // let _stake_authority_info = next_account_info(account_info_iter);
+
+    let clock = *clock_from_account_info(clock_info)?;
+    let stake_history = &StakeHistorySysvar(clock.epoch);
+
+    if source_stake_account_info.key() == destination_stake_account_info.key() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let destination_merge_kind = MergeKind::get_if_mergeable(
+        &get_stake_state(destination_stake_account_info)?,
+        destination_stake_account_info.lamports(),
+        &clock,
+        stake_history,
+    )?;
+
+    // Authorized staker is allowed to split/merge accounts
+    destination_merge_kind
+        .meta()
+        .authorized
+        .check(signers, StakeAuthorize::Staker)
+        .map_err(|_| ProgramError::MissingRequiredSignature)?;
+
+    let source_merge_kind = MergeKind::get_if_mergeable(
+        &get_stake_state(source_stake_account_info)?,
+        source_stake_account_info.lamports(),
+        &clock,
+        stake_history,
+    )?;
+
+    if let Some(merged_state) = destination_merge_kind.merge(source_merge_kind, &clock)? {
+        set_stake_state(destination_stake_account_info, &merged_state)?;
+    }
+
+    // Source is about to be drained, deinitialize its state
+    set_stake_state(source_stake_account_info, &StakeStateV2::Uninitialized)?;
+
+    // Drain the source stake account
+    relocate_lamports(
+        source_stake_account_info,
+        destination_stake_account_info,
+        source_stake_account_info.lamports(),
+    )?;
+
+    Ok(())
+}
@@ -1,16 +1,52 @@
 use pinocchio::program_error::ProgramError;
 
 
+pub mod authorize;
+pub mod authorize_checked_with_seed;
+pub mod authorize_with_seed;
 pub mod authorized_checked;
+pub mod deactivate;
+pub mod deactivate_delinquent;
+pub mod delegate;
+pub mod get_minimum_delegation;
+pub mod initialize;
+pub mod initialize_checked;
+pub mod merge;
+pub mod move_lamports;
+pub mod move_stake;
 pub mod split;
 pub mod redelegate;
+pub mod set_lockup;
+pub mod withdraw;
 
+pub use authorize::*;
+pub use authorize_checked_with_seed::*;
+pub use authorize_with_seed::*;
 pub use authorized_checked::*;
+pub use deactivate::*;
+pub use deactivate_delinquent::*;
+pub use delegate::*;
+pub use get_minimum_delegation::*;
+pub use initialize::*;
+pub use initialize_checked::*;
+pub use merge::*;
+pub use move_lamports::*;
+pub use move_stake::*;
 pub use split::*;
 pub use redelegate::*;
+pub use set_lockup::*;
+pub use withdraw::*;
 
+/// Reads the `u64` lamport argument of `Split`, `Withdraw`, `MoveStake` and
+/// `MoveLamports` from the bytes following the discriminator.
+pub fn parse_lamports(data: &[u8]) -> Result<u64, ProgramError> {
+    data.get(..8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
 
-#[repr(u8)]
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StakeInstruction {
     Initialize,
     Authorize,
@@ -33,11 +69,12 @@ pub enum StakeInstruction {
     MoveLamports,
 }
 
-impl TryFrom<&u8> for StakeInstruction {
+/// Decodes the bincode tag of a native stake instruction, a u32 LE
+impl TryFrom<&[u8; 4]> for StakeInstruction {
     type Error = ProgramError;
 
-    fn try_from(value: &u8) -> Result<Self, Self::Error> {
-        match *value {
+    fn try_from(value: &[u8; 4]) -> Result<Self, Self::Error> {
+        match u32::from_le_bytes(*value) {
             0 => Ok(StakeInstruction::Initialize),
             1 => Ok(StakeInstruction::Authorize),
             2 => Ok(StakeInstruction::DelegateStake),
@@ -62,6 +99,52 @@ impl TryFrom<&u8> for StakeInstruction {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::StakeInstruction;
+    use pinocchio::program_error::ProgramError;
+    use solana_sdk::stake::instruction::StakeInstruction as NativeStakeInstruction;
+
+    #[test]
+    fn test_discriminator_matches_native() {
+        #[allow(deprecated)]
+        let instructions = [
+            (
+                NativeStakeInstruction::GetMinimumDelegation,
+                StakeInstruction::GetMinimumDelegation,
+            ),
+            (NativeStakeInstruction::Withdraw(1), StakeInstruction::Withdraw),
+            (NativeStakeInstruction::Redelegate, StakeInstruction::Redelegate),
+            (NativeStakeInstruction::MoveStake(1), StakeInstruction::MoveStake),
+            (
+                NativeStakeInstruction::MoveLamports(1),
+                StakeInstruction::MoveLamports,
+            ),
+        ];
+
+        for (native, expected) in instructions {
+            let data = bincode::serialize(&native).unwrap();
+            let tag = data.first_chunk::<4>().unwrap();
+
+            assert_eq!(StakeInstruction::try_from(tag), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn test_discriminator_rejects_unknown_tags() {
+        for tag in (0..=17u32).map(u32::to_le_bytes) {
+            assert!(StakeInstruction::try_from(&tag).is_ok());
+        }
+
+        for tag in [18, 255, 256, 1 << 24, 0x0100_0004, u32::MAX] {
+            assert_eq!(
+                StakeInstruction::try_from(&u32::to_le_bytes(tag)),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+}
+
 mod idl_gen {
     #[derive(shank::ShankInstruction)]
     enum _MyProgramInstruction {
@@ -0,0 +1,46 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::state::{
+    move_stake_or_lamports_shared_checks, next_account_info, relocate_lamports,
+    MergeKind,
+};
+
+pub fn process_move_lamports(accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 3 accounts
+    let source_stake_account_info = next_account_info(account_info_iter)?;
+    let destination_stake_account_info = next_account_info(account_info_iter)?;
+    let stake_authority_info = next_account_info(account_info_iter)?;
+
+    let (source_merge_kind, _) = move_stake_or_lamports_shared_checks(
+        source_stake_account_info,
+        lamports,
+        destination_stake_account_info,
+        stake_authority_info,
+    )?;
+
+    // only lamports that are neither staked nor part of the reserve can move
+    let source_free_lamports = match source_merge_kind {
+        MergeKind::FullyActive(source_meta, source_stake) => source_stake_account_info
+            .lamports()
+            .saturating_sub(source_stake.delegation.stake.get())
+            .saturating_sub(source_meta.rent_exempt_reserve()),
+        MergeKind::Inactive(source_meta, source_lamports, _) => {
+            source_lamports.saturating_sub(source_meta.rent_exempt_reserve())
+        }
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    if lamports > source_free_lamports {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    relocate_lamports(
+        source_stake_account_info,
+        destination_stake_account_info,
+        lamports,
+    )?;
+
+    Ok(())
+}
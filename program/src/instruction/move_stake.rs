@@ -0,0 +1,138 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    error::StakeError,
+    state::{
+        get_minimum_delegation, merge_delegation_stake_and_credits_observed,
+        move_stake_or_lamports_shared_checks, next_account_info, relocate_lamports,
+        set_stake_state, MergeKind, StakeFlags, StakeStateV2,
+    },
+};
+
+pub fn process_move_stake(accounts: &[AccountInfo], lamports: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 3 accounts
+    let source_stake_account_info = next_account_info(account_info_iter)?;
+    let destination_stake_account_info = next_account_info(account_info_iter)?;
+    let stake_authority_info = next_account_info(account_info_iter)?;
+
+    let (source_merge_kind, destination_merge_kind) = move_stake_or_lamports_shared_checks(
+        source_stake_account_info,
+        lamports,
+        destination_stake_account_info,
+        stake_authority_info,
+    )?;
+
+    // ensure source and destination are the right size for the current version of StakeState
+    // this a safeguard in case there is a new version of the struct that cannot fit into an old account
+    if source_stake_account_info.data_len() != StakeStateV2::size_of()
+        || destination_stake_account_info.data_len() != StakeStateV2::size_of()
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // source must be fully active
+    let MergeKind::FullyActive(source_meta, mut source_stake) = source_merge_kind else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+
+    let minimum_delegation = get_minimum_delegation();
+    let source_effective_stake = source_stake.delegation.stake.get();
+
+    // source cannot move more stake than it has, regardless of how many lamports it has
+    let source_final_stake = source_effective_stake
+        .checked_sub(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+
+    // unless all stake is being moved, source must retain at least the minimum delegation
+    if source_final_stake != 0 && source_final_stake < minimum_delegation {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // destination must be fully active or fully inactive
+    let destination_meta = match destination_merge_kind {
+        MergeKind::FullyActive(destination_meta, mut destination_stake) => {
+            // if active, destination must be delegated to the same vote account as source
+            if source_stake.delegation.voter_pubkey != destination_stake.delegation.voter_pubkey {
+                return Err(StakeError::VoteAddressMismatch.into());
+            }
+
+            let destination_effective_stake = destination_stake.delegation.stake.get();
+            let destination_final_stake = destination_effective_stake
+                .checked_add(lamports)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+
+            // ensure destination meets miniumum delegation
+            // since it is already active, this only really applies if the minimum is raised
+            if destination_final_stake < minimum_delegation {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            merge_delegation_stake_and_credits_observed(
+                &mut destination_stake,
+                lamports,
+                source_stake.credits_observed(),
+            )?;
+
+            // StakeFlags::empty() is valid here because the only existing stake flag,
+            // MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED, does not apply to active stakes
+            set_stake_state(
+                destination_stake_account_info,
+                &StakeStateV2::Stake(destination_meta, destination_stake, StakeFlags::empty()),
+            )?;
+
+            destination_meta
+        }
+        MergeKind::Inactive(destination_meta, _, _) => {
+            // if destination is inactive, it must be given at least the minimum delegation
+            if lamports < minimum_delegation {
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            let mut destination_stake = source_stake;
+            destination_stake.delegation.set_stake(lamports);
+
+            // StakeFlags::empty() is valid here because the only existing stake flag,
+            // MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED, is cleared when a stake is activated
+            set_stake_state(
+                destination_stake_account_info,
+                &StakeStateV2::Stake(destination_meta, destination_stake, StakeFlags::empty()),
+            )?;
+
+            destination_meta
+        }
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    if source_final_stake == 0 {
+        set_stake_state(
+            source_stake_account_info,
+            &StakeStateV2::Initialized(source_meta),
+        )?;
+    } else {
+        source_stake.delegation.set_stake(source_final_stake);
+
+        // StakeFlags::empty() is valid here because the only existing stake flag,
+        // MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED, does not apply to active stakes
+        set_stake_state(
+            source_stake_account_info,
+            &StakeStateV2::Stake(source_meta, source_stake, StakeFlags::empty()),
+        )?;
+    }
+
+    relocate_lamports(
+        source_stake_account_info,
+        destination_stake_account_info,
+        lamports,
+    )?;
+
+    // this should be impossible, but because we do all our math with delegations, best to guard it
+    if source_stake_account_info.lamports() < source_meta.rent_exempt_reserve()
+        || destination_stake_account_info.lamports() < destination_meta.rent_exempt_reserve()
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    Ok(())
+}
@@ -14,7 +14,7 @@ use crate::state::load_acc_mut_unchecked;
 use crate::{
     state::{
         utils::{load_ix_data, DataLen},
-        RedelegateState,
+        PodU64, RedelegateState,
     },
 };
 
@@ -22,7 +22,7 @@ use crate::{
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct StartRedelegationIxData {
     pub new_validator: Pubkey,
-    pub stake_amount: [u8; 8],
+    pub stake_amount: PodU64,
     pub bump: u8,
 }
 
@@ -57,7 +57,7 @@ pub fn process_start_redelegation(accounts: &[AccountInfo], data: &[u8]) -> Prog
         return Err(ProgramError::InvalidArgument);
     }
 
-    redelegate_state.start_redelegation(&ix_data)
+    redelegate_state.start_redelegation(ix_data)
 }
 
 pub fn process_complete_redelegation(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
@@ -100,9 +100,9 @@ pub fn process_complete_redelegation(accounts: &[AccountInfo], data: &[u8]) -> P
 
     let ix_data = unsafe { load_ix_data::<StartRedelegationIxData>(data)? };
 
-    redelegate_state.complete_redelegation();
+    redelegate_state.complete_redelegation()?;
 
-    let stake_amount = u64::from_le_bytes(ix_data.stake_amount);
+    let stake_amount = ix_data.stake_amount.get();
     if stake_amount > vault_acc.amount() {
         (TransferChecked{
             from: owner_ata,
@@ -0,0 +1,219 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvars::{clock::Clock, Sysvar},
+    ProgramResult,
+};
+
+use crate::{
+    consts::MAX_SIGNERS,
+    state::{
+        collect_signers, get_stake_state, next_account_info, set_stake_state, to_program_error,
+        Epoch, LockupArgs, LockupCheckedArgs, StakeStateV2, UnixTimestamp,
+    },
+};
+
+/// Reads a bincode `Option<[u8; N]>`: a `0`/`1` tag byte, followed by the value
+/// when present. Advances `data` past what was read.
+fn read_option<const N: usize>(data: &mut &[u8]) -> Result<Option<[u8; N]>, ProgramError> {
+    let (tag, rest) = data
+        .split_first()
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    match tag {
+        0 => {
+            *data = rest;
+            Ok(None)
+        }
+        1 => {
+            let (value, rest) = rest
+                .split_first_chunk::<N>()
+                .ok_or(ProgramError::InvalidInstructionData)?;
+            *data = rest;
+            Ok(Some(*value))
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+/// Decodes the bincode `LockupArgs` following the discriminator.
+///
+/// Trailing bytes are ignored, as bincode does for the native program.
+pub fn parse_lockup_args(mut data: &[u8]) -> Result<LockupArgs, ProgramError> {
+    Ok(LockupArgs {
+        unix_timestamp: read_option(&mut data)?.map(UnixTimestamp::from_le_bytes),
+        epoch: read_option(&mut data)?.map(Epoch::from_le_bytes),
+        custodian: read_option(&mut data)?,
+    })
+}
+
+/// Decodes the bincode `LockupCheckedArgs` following the discriminator.
+pub fn parse_lockup_checked_args(mut data: &[u8]) -> Result<LockupCheckedArgs, ProgramError> {
+    Ok(LockupCheckedArgs {
+        unix_timestamp: read_option(&mut data)?.map(UnixTimestamp::from_le_bytes),
+        epoch: read_option(&mut data)?.map(Epoch::from_le_bytes),
+    })
+}
+
+pub fn process_set_lockup(accounts: &[AccountInfo], lockup: &LockupArgs) -> ProgramResult {
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 1 account
+    let stake_account_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    // let _old_withdraw_or_lockup_authority_info = next_account_info(account_info_iter);
+
+    let clock = Clock::get()?;
+
+    do_set_lockup(stake_account_info, signers, lockup, &clock)
+}
+
+pub fn process_set_lockup_checked(
+    accounts: &[AccountInfo],
+    lockup_checked: &LockupCheckedArgs,
+) -> ProgramResult {
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 1 account
+    let stake_account_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    let _old_withdraw_or_lockup_authority_info = next_account_info(account_info_iter);
+    let option_new_lockup_authority_info = next_account_info(account_info_iter).ok();
+
+    let clock = Clock::get()?;
+
+    let custodian = match option_new_lockup_authority_info {
+        Some(new_lockup_authority_info) if new_lockup_authority_info.is_signer() => {
+            Some(*new_lockup_authority_info.key())
+        }
+        Some(_) => return Err(ProgramError::MissingRequiredSignature),
+        None => None,
+    };
+
+    let lockup = LockupArgs {
+        unix_timestamp: lockup_checked.unix_timestamp,
+        epoch: lockup_checked.epoch,
+        custodian,
+    };
+
+    do_set_lockup(stake_account_info, signers, &lockup, &clock)
+}
+
+fn do_set_lockup(
+    stake_account_info: &AccountInfo,
+    signers: &[Pubkey],
+    lockup: &LockupArgs,
+    clock: &Clock,
+) -> ProgramResult {
+    match get_stake_state(stake_account_info)? {
+        StakeStateV2::Initialized(mut meta) => {
+            meta.set_lockup(lockup, signers, clock)
+                .map_err(to_program_error)?;
+
+            set_stake_state(stake_account_info, &StakeStateV2::Initialized(meta))
+        }
+        StakeStateV2::Stake(mut meta, stake, stake_flags) => {
+            meta.set_lockup(lockup, signers, clock)
+                .map_err(to_program_error)?;
+
+            set_stake_state(
+                stake_account_info,
+                &StakeStateV2::Stake(meta, stake, stake_flags),
+            )
+        }
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lockup_args, parse_lockup_checked_args};
+    use crate::state::{Epoch, LockupArgs, LockupCheckedArgs, UnixTimestamp};
+    use pinocchio::program_error::ProgramError;
+    use solana_sdk::{pubkey::Pubkey, stake::instruction};
+
+    #[test]
+    fn test_parse_lockup_args() {
+        let custodian = Pubkey::new_unique();
+
+        for (unix_timestamp, epoch, custodian) in [
+            (None, None, None),
+            (Some(-1), None, None),
+            (None, Some(u64::MAX), None),
+            (None, None, Some(custodian)),
+            (Some(i64::MIN), Some(7), Some(custodian)),
+        ] {
+            let data = bincode::serialize(&instruction::LockupArgs {
+                unix_timestamp,
+                epoch,
+                custodian,
+            })
+            .unwrap();
+
+            assert_eq!(
+                parse_lockup_args(&data),
+                Ok(LockupArgs {
+                    unix_timestamp: unix_timestamp.map(UnixTimestamp::from),
+                    epoch: epoch.map(Epoch::from),
+                    custodian: custodian.map(Pubkey::to_bytes),
+                })
+            );
+
+            let data = bincode::serialize(&instruction::LockupCheckedArgs {
+                unix_timestamp,
+                epoch,
+            })
+            .unwrap();
+
+            assert_eq!(
+                parse_lockup_checked_args(&data),
+                Ok(LockupCheckedArgs {
+                    unix_timestamp: unix_timestamp.map(UnixTimestamp::from),
+                    epoch: epoch.map(Epoch::from),
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_lockup_args_invalid() {
+        let data = bincode::serialize(&instruction::LockupArgs {
+            unix_timestamp: Some(1),
+            epoch: Some(2),
+            custodian: Some(Pubkey::new_unique()),
+        })
+        .unwrap();
+
+        // trailing bytes are ignored, like bincode does
+        let mut trailing = data.clone();
+        trailing.push(9);
+        assert_eq!(parse_lockup_args(&trailing), parse_lockup_args(&data));
+
+        for len in 0..data.len() {
+            assert_eq!(
+                parse_lockup_args(&data[..len]),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+
+        let mut bad_tag = data.clone();
+        bad_tag[0] = 2;
+        assert_eq!(
+            parse_lockup_args(&bad_tag),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            parse_lockup_checked_args(&[0, 2]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -1,10 +1,8 @@
-use core::ops::Deref;
-
 use crate::{
-    consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+    consts::{MAX_SIGNERS, PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH},
     error::StakeError,
     state::{
-        bytes_to_u64, get_minimum_delegation, get_stake_state, relocate_lamports, set_stake_state,
+        get_minimum_delegation, get_stake_state, relocate_lamports, set_stake_state,
         to_program_error, validate_split_amount, StakeAuthorize, StakeHistorySysvar, StakeStateV2,
     },
 };
@@ -29,8 +27,9 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
 
     //Instead of an HashSet we can use an array.
     //Collect signers will return the array length and fill this signers array instead of returning the HashSet
-    let mut signers_arr = [Pubkey::default(); 32];
-    let _signers = collect_signers(accounts, &mut signers_arr)?;
+    let mut signers_arr = [Pubkey::default(); MAX_SIGNERS];
+    let signers_len = collect_signers(accounts, &mut signers_arr)?;
+    let signers = &signers_arr[..signers_len];
     let account_info_iter = &mut accounts.iter();
 
     //----------- Finish Update ----------
@@ -50,7 +49,7 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
         return Err(ProgramError::InvalidAccountData);
     }
 
-    if let StakeStateV2::Uninitialized = get_stake_state(destination_stake_account_info)?.deref() {
+    if let StakeStateV2::Uninitialized = get_stake_state(destination_stake_account_info)? {
         // we can split into this
     } else {
         return Err(ProgramError::InvalidAccountData);
@@ -63,29 +62,29 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
         return Err(ProgramError::InsufficientFunds);
     }
 
-    match get_stake_state(source_stake_account_info)?.deref() {
+    match &get_stake_state(source_stake_account_info)? {
         StakeStateV2::Stake(source_meta, mut source_stake, stake_flags) => {
             source_meta
                 .authorized
-                .check(&signers_arr, StakeAuthorize::Staker)
+                .check(signers, StakeAuthorize::Staker)
                 .map_err(to_program_error)?;
 
             let minimum_delegation = get_minimum_delegation();
 
             let status = source_stake.delegation.stake_activating_and_deactivating(
-                clock.epoch.to_be_bytes(),
+                clock.epoch.into(),
                 stake_history,
                 PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
             );
 
-            let is_active = bytes_to_u64(status.effective) > 0;
+            let is_active = status.effective.get() > 0;
 
             // NOTE this function also internally summons Rent via syscall
             let validated_split_info = validate_split_amount(
                 source_lamport_balance,
                 destination_lamport_balance,
                 split_lamports,
-                &source_meta,
+                source_meta,
                 destination_data_len,
                 minimum_delegation,
                 is_active,
@@ -108,14 +107,17 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
                     // original rent_exempt_reserve and the split_rent_exempt_reserve, in order
                     // to prevent magic activation of stake by splitting between accounts of
                     // different sizes.
-                    let remaining_stake_delta = split_lamports
-                        .saturating_sub(u64::from_le_bytes(source_meta.rent_exempt_reserve));
+                    let remaining_stake_delta =
+                        split_lamports.saturating_sub(source_meta.rent_exempt_reserve.get());
                     (remaining_stake_delta, remaining_stake_delta)
                 } else {
                     // Otherwise, the new split stake should reflect the entire split
                     // requested, less any lamports needed to cover the
                     // split_rent_exempt_reserve.
-                    if u64::from_le_bytes(source_stake.delegation.stake)
+                    if source_stake
+                        .delegation
+                        .stake
+                        .get()
                         .saturating_sub(split_lamports)
                         < minimum_delegation
                     {
@@ -140,9 +142,8 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
                 source_stake.split(remaining_stake_delta, split_stake_amount)?;
 
             let mut destination_meta = *source_meta;
-            destination_meta.rent_exempt_reserve = validated_split_info
-                .destination_rent_exempt_reserve
-                .to_be_bytes();
+            destination_meta.rent_exempt_reserve =
+                validated_split_info.destination_rent_exempt_reserve.into();
 
             set_stake_state(
                 source_stake_account_info,
@@ -157,7 +158,7 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
         StakeStateV2::Initialized(source_meta) => {
             source_meta
                 .authorized
-                .check(&signers_arr, StakeAuthorize::Staker)
+                .check(signers, StakeAuthorize::Staker)
                 .map_err(to_program_error)?;
 
             // NOTE this function also internally summons Rent via syscall
@@ -165,16 +166,15 @@ pub fn process_split(accounts: &[AccountInfo], split_lamports: u64) -> ProgramRe
                 source_lamport_balance,
                 destination_lamport_balance,
                 split_lamports,
-                &source_meta,
+                source_meta,
                 destination_data_len,
                 0,     // additional_required_lamports
                 false, // is_active
             )?;
 
             let mut destination_meta = *source_meta;
-            destination_meta.rent_exempt_reserve = validated_split_info
-                .destination_rent_exempt_reserve
-                .to_le_bytes();
+            destination_meta.rent_exempt_reserve =
+                validated_split_info.destination_rent_exempt_reserve.into();
 
             set_stake_state(
                 destination_stake_account_info,
@@ -0,0 +1,119 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+use crate::{
+    consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+    error::StakeError,
+    state::{
+        checked_add, clock_from_account_info, get_stake_state, next_account_info,
+        relocate_lamports, set_stake_state, to_program_error, Lockup, StakeAuthorize,
+        StakeHistorySysvar, StakeStateV2,
+    },
+};
+
+pub fn process_withdraw(accounts: &[AccountInfo], withdraw_lamports: u64) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    // native asserts: 5 accounts (2 sysvars)
+    let source_stake_account_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    let _stake_history_info = next_account_info(account_info_iter)?;
+    let withdraw_authority_info = next_account_info(account_info_iter)?;
+
+    // other accounts
+    let option_lockup_authority_info = next_account_info(account_info_iter).ok();
+
+    let clock = *clock_from_account_info(clock_info)?;
+    let stake_history = &StakeHistorySysvar(clock.epoch);
+
+    // unlike most processors, native only considers the withdraw authority and
+    // the custodian as signers, and requires both signatures before reading the
+    // stake account
+    if !withdraw_authority_info.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let custodian = match option_lockup_authority_info {
+        Some(custodian_info) if custodian_info.is_signer() => Some(custodian_info.key()),
+        Some(_) => return Err(ProgramError::MissingRequiredSignature),
+        None => None,
+    };
+
+    let mut signers_arr = [*withdraw_authority_info.key(); 2];
+    let signers_len = if let Some(custodian) = custodian {
+        signers_arr[1] = *custodian;
+        2
+    } else {
+        1
+    };
+    let signers = &signers_arr[..signers_len];
+
+    let (lockup, reserve, is_staked) = match get_stake_state(source_stake_account_info)? {
+        StakeStateV2::Stake(meta, stake, _stake_flags) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Withdrawer)
+                .map_err(to_program_error)?;
+
+            // if we have a deactivation epoch and we're in cooldown
+            let staked = if clock.epoch >= stake.delegation.deactivation_epoch() {
+                stake.delegation.stake(
+                    clock.epoch.into(),
+                    stake_history,
+                    PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+                )
+            } else {
+                // Assume full stake if the stake account hasn't been
+                //  de-activated, because in the future the exposed stake
+                //  might be higher than stake.stake() due to warmup
+                stake.delegation.stake.get()
+            };
+
+            let staked_and_reserve = checked_add(staked, meta.rent_exempt_reserve())?;
+            (meta.lockup, staked_and_reserve, staked != 0)
+        }
+        StakeStateV2::Initialized(meta) => {
+            meta.authorized
+                .check(signers, StakeAuthorize::Withdrawer)
+                .map_err(to_program_error)?;
+
+            // stake accounts must have a balance >= rent_exempt_reserve
+            (meta.lockup, meta.rent_exempt_reserve(), false)
+        }
+        StakeStateV2::Uninitialized => {
+            if !signers.contains(source_stake_account_info.key()) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+
+            (Lockup::default(), 0, false)
+        }
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    if lockup.is_in_force(&clock, custodian) {
+        return Err(StakeError::LockupInForce.into());
+    }
+
+    let stake_account_lamports = source_stake_account_info.lamports();
+    if withdraw_lamports == stake_account_lamports {
+        // if the stake is active, we mustn't allow the account to go away
+        if is_staked {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // Deinitialize state upon zero balance
+        set_stake_state(source_stake_account_info, &StakeStateV2::Uninitialized)?;
+    } else {
+        // a partial withdrawal must not deplete the reserve
+        let withdraw_lamports_and_reserve = checked_add(withdraw_lamports, reserve)?;
+        if withdraw_lamports_and_reserve > stake_account_lamports {
+            return Err(ProgramError::InsufficientFunds);
+        }
+    }
+
+    relocate_lamports(
+        source_stake_account_info,
+        destination_info,
+        withdraw_lamports,
+    )?;
+
+    Ok(())
+}
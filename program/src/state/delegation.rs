@@ -1,6 +1,6 @@
 use pinocchio::pubkey::Pubkey;
 
-use super::{bytes_to_u64, warmup_cooldown_rate, Epoch, StakeHistoryEntry, StakeHistoryGetEntry};
+use super::{rate_limited_stake_change, Epoch, PodU64, StakeHistoryEntry, StakeHistoryGetEntry};
 
 pub type StakeActivationStatus = StakeHistoryEntry;
 
@@ -10,7 +10,7 @@ pub struct Delegation {
     /// to whom the stake is delegated
     pub voter_pubkey: Pubkey,
     /// activated stake amount, set at delegate() time
-    pub stake: [u8; 8], // u64
+    pub stake: PodU64,
     /// epoch at which this stake was activated, std::Epoch::MAX if is a bootstrap stake
     pub activation_epoch: Epoch,
     /// epoch the stake was deactivated, std::Epoch::MAX if not deactivated
@@ -20,33 +20,33 @@ pub struct Delegation {
         since = "1.16.7",
         note = "Please use `solana_sdk::stake::state::warmup_cooldown_rate()` instead"
     )]
-    warmup_cooldown_rate: [u8; 8], //f64
+    warmup_cooldown_rate: [u8; 8], // f64, not an integer so stays raw bytes
 }
 
 impl Delegation {
     pub fn new(voter_pubkey: &Pubkey, stake: u64, activation_epoch: Epoch) -> Self {
         Self {
             voter_pubkey: *voter_pubkey,
-            stake: stake.to_le_bytes(),
+            stake: stake.into(),
             activation_epoch,
             ..Delegation::default()
         }
     }
 
     pub fn is_bootstrap(&self) -> bool {
-        u64::from_le_bytes(self.activation_epoch) == u64::MAX
+        self.activation_epoch == Epoch::MAX
     }
 
     #[inline(always)]
     pub fn set_stake(&mut self, stake: u64) {
-        self.stake = stake.to_le_bytes();
+        self.stake = stake.into();
     }
 
     /*
     // Implemented by Stanislav
     #[inline(always)]
     pub fn stake(&self) -> u64 {
-        u64::from_le_bytes(self.stake)
+        self.stake.get()
     }
 
      */
@@ -57,10 +57,9 @@ impl Delegation {
         history: &T,
         new_rate_activation_epoch: Option<Epoch>,
     ) -> u64 {
-        let result = self
-            .stake_activating_and_deactivating(epoch, history, new_rate_activation_epoch)
-            .effective;
-        u64::from_be_bytes(result)
+        self.stake_activating_and_deactivating(epoch, history, new_rate_activation_epoch)
+            .effective
+            .get()
     }
 
     #[allow(clippy::comparison_chain)]
@@ -78,18 +77,18 @@ impl Delegation {
         if target_epoch < self.deactivation_epoch {
             // not deactivated
             if activating_stake == 0 {
-                StakeActivationStatus::with_effective(effective_stake.to_le_bytes())
+                StakeActivationStatus::with_effective(effective_stake)
             } else {
                 StakeActivationStatus::with_effective_and_activating(
-                    effective_stake.to_le_bytes(),
-                    activating_stake.to_le_bytes(),
+                    effective_stake,
+                    activating_stake,
                 )
             }
         } else if target_epoch == self.deactivation_epoch {
             // can only deactivate what's activated
             StakeActivationStatus::with_deactivating(effective_stake)
         } else if let Some((history, mut prev_epoch, mut prev_cluster_stake)) = history
-            .get_entry(bytes_to_u64(self.deactivation_epoch))
+            .get_entry(self.deactivation_epoch.get())
             .map(|cluster_stake_at_deactivation_epoch| {
                 (
                     history,
@@ -104,29 +103,25 @@ impl Delegation {
             // current effective stake is updated using its previous epoch's cluster stake
             let mut current_epoch;
             let mut current_effective_stake = effective_stake;
-            let prev_cluster_stake_deactivating = bytes_to_u64(prev_cluster_stake.deactivating);
-            let prev_cluster_stake_effective = bytes_to_u64(prev_cluster_stake.effective);
-
             loop {
-                current_epoch = bytes_to_u64(prev_epoch) + 1;
+                current_epoch = prev_epoch.get() + 1;
                 // if there is no deactivating stake at prev epoch, we should have been
                 // fully undelegated at this moment
-                if bytes_to_u64(prev_cluster_stake.deactivating) == 0 {
+                if prev_cluster_stake.deactivating.get() == 0 {
                     break;
                 }
 
                 // I'm trying to get to zero, how much of the deactivation in stake
-                //   this account is entitled to take
-                let weight =
-                    current_effective_stake as f64 / prev_cluster_stake_deactivating as f64;
-                let warmup_cooldown_rate =
-                    warmup_cooldown_rate(current_epoch.to_be_bytes(), new_rate_activation_epoch);
-
-                // portion of newly not-effective cluster stake I'm entitled to at current epoch
-                let newly_not_effective_cluster_stake =
-                    prev_cluster_stake_effective as f64 * warmup_cooldown_rate;
-                let newly_not_effective_stake =
-                    ((weight * newly_not_effective_cluster_stake) as u64).max(1);
+                //   this account is entitled to take, out of the
+                //   newly not-effective cluster stake at current epoch
+                let newly_not_effective_stake = rate_limited_stake_change(
+                    current_effective_stake,
+                    prev_cluster_stake.deactivating.get(),
+                    prev_cluster_stake.effective.get(),
+                    current_epoch.into(),
+                    new_rate_activation_epoch,
+                )
+                .max(1);
 
                 current_effective_stake =
                     current_effective_stake.saturating_sub(newly_not_effective_stake);
@@ -134,11 +129,11 @@ impl Delegation {
                     break;
                 }
 
-                if current_epoch >= bytes_to_u64(target_epoch) {
+                if current_epoch >= target_epoch.get() {
                     break;
                 }
                 if let Some(current_cluster_stake) = history.get_entry(current_epoch) {
-                    prev_epoch = current_epoch.to_le_bytes();
+                    prev_epoch = current_epoch.into();
                     prev_cluster_stake = current_cluster_stake;
                 } else {
                     break;
@@ -164,19 +159,19 @@ impl Delegation {
 
         if self.is_bootstrap() {
             // fully effective immediately
-            (bytes_to_u64(delegated_stake), 0)
+            (delegated_stake.get(), 0)
         } else if self.activation_epoch == self.deactivation_epoch {
             // activated but instantly deactivated; no stake at all regardless of target_epoch
             // this must be after the bootstrap check and before all-is-activating check
             (0, 0)
         } else if target_epoch == self.activation_epoch {
             // all is activating
-            (0, bytes_to_u64(delegated_stake))
+            (0, delegated_stake.get())
         } else if target_epoch < self.activation_epoch {
             // not yet enabled
             (0, 0)
         } else if let Some((history, mut prev_epoch, mut prev_cluster_stake)) = history
-            .get_entry(bytes_to_u64(self.activation_epoch))
+            .get_entry(self.activation_epoch.get())
             .map(|cluster_stake_at_activation_epoch| {
                 (
                     history,
@@ -192,41 +187,40 @@ impl Delegation {
             let mut current_epoch;
             let mut current_effective_stake = 0;
             loop {
-                current_epoch = bytes_to_u64(prev_epoch) + 1;
+                current_epoch = prev_epoch.get() + 1;
                 // if there is no activating stake at prev epoch, we should have been
                 // fully effective at this moment
-                if bytes_to_u64(prev_cluster_stake.activating) == 0 {
+                if prev_cluster_stake.activating.get() == 0 {
                     break;
                 }
 
                 // how much of the growth in stake this account is
-                //  entitled to take
+                //  entitled to take, out of the newly effective
+                //  cluster stake at current epoch
                 let remaining_activating_stake =
-                    u64::from_le_bytes(delegated_stake) - current_effective_stake;
-                let weight = remaining_activating_stake as f64
-                    / bytes_to_u64(prev_cluster_stake.activating) as f64;
-                let warmup_cooldown_rate =
-                    warmup_cooldown_rate(current_epoch.to_le_bytes(), new_rate_activation_epoch);
-
-                // portion of newly effective cluster stake I'm entitled to at current epoch
-                let newly_effective_cluster_stake =
-                    bytes_to_u64(prev_cluster_stake.effective) as f64 * warmup_cooldown_rate;
-                let newly_effective_stake =
-                    ((weight * newly_effective_cluster_stake) as u64).max(1);
+                    delegated_stake.get() - current_effective_stake;
+                let newly_effective_stake = rate_limited_stake_change(
+                    remaining_activating_stake,
+                    prev_cluster_stake.activating.get(),
+                    prev_cluster_stake.effective.get(),
+                    current_epoch.into(),
+                    new_rate_activation_epoch,
+                )
+                .max(1);
 
                 current_effective_stake += newly_effective_stake;
-                if current_effective_stake >= bytes_to_u64(delegated_stake) {
-                    current_effective_stake = bytes_to_u64(delegated_stake);
+                if current_effective_stake >= delegated_stake.get() {
+                    current_effective_stake = delegated_stake.get();
                     break;
                 }
 
-                if current_epoch >= bytes_to_u64(target_epoch)
-                    || current_epoch >= bytes_to_u64(self.deactivation_epoch)
+                if current_epoch >= target_epoch.get()
+                    || current_epoch >= self.deactivation_epoch.get()
                 {
                     break;
                 }
                 if let Some(current_cluster_stake) = history.get_entry(current_epoch) {
-                    prev_epoch = current_epoch.to_le_bytes();
+                    prev_epoch = current_epoch.into();
                     prev_cluster_stake = current_cluster_stake;
                 } else {
                     break;
@@ -235,32 +229,32 @@ impl Delegation {
 
             (
                 current_effective_stake,
-                bytes_to_u64(delegated_stake) - current_effective_stake,
+                delegated_stake.get() - current_effective_stake,
             )
         } else {
             // no history or I've dropped out of history, so assume fully effective
-            (bytes_to_u64(delegated_stake), 0)
+            (delegated_stake.get(), 0)
         }
     }
 
     #[inline(always)]
     pub fn set_activation_epoch(&mut self, activation_epoch: u64) {
-        self.activation_epoch = activation_epoch.to_le_bytes();
+        self.activation_epoch = activation_epoch.into();
     }
 
     #[inline(always)]
     pub fn activation_epoch(&self) -> u64 {
-        u64::from_le_bytes(self.activation_epoch)
+        self.activation_epoch.get()
     }
 
     #[inline(always)]
     pub fn set_deactivation_epoch(&mut self, deactivation_epoch: u64) {
-        self.deactivation_epoch = deactivation_epoch.to_le_bytes();
+        self.deactivation_epoch = deactivation_epoch.into();
     }
 
     #[inline(always)]
     pub fn deactivation_epoch(&self) -> u64 {
-        u64::from_le_bytes(self.deactivation_epoch)
+        self.deactivation_epoch.get()
     }
 }
 
@@ -271,10 +265,499 @@ impl Default for Delegation {
         #[allow(deprecated)]
         Self {
             voter_pubkey: Pubkey::default(),
-            stake: 0u64.to_le_bytes(),
-            activation_epoch: 0u64.to_le_bytes(),
-            deactivation_epoch: u64::MAX.to_le_bytes(),
+            stake: PodU64::ZERO,
+            activation_epoch: Epoch::ZERO,
+            deactivation_epoch: Epoch::MAX,
             warmup_cooldown_rate: DEFAULT_WARMUP_COOLDOWN_RATE.to_le_bytes(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StakeHistory;
+    use solana_sdk::{
+        stake::state::Delegation as NativeDelegation,
+        stake_history::{StakeHistory as NativeStakeHistory, StakeHistoryEntry as NativeEntry},
+    };
+
+    const EPOCHS: u64 = 40;
+
+    fn entry(native: &NativeEntry) -> StakeHistoryEntry {
+        StakeHistoryEntry {
+            effective: native.effective.into(),
+            activating: native.activating.into(),
+            deactivating: native.deactivating.into(),
+        }
+    }
+
+    /// Runs a cluster of `delegations`, given as (stake, activation epoch,
+    /// deactivation epoch), through `EPOCHS` epochs of native history, and checks
+    /// every delegation against native at every epoch
+    fn check_against_native(
+        delegations: &[(u64, u64, u64)],
+        new_rate_activation_epoch: Option<u64>,
+    ) {
+        let native_delegations: Vec<NativeDelegation> = delegations
+            .iter()
+            .map(|&(stake, activation_epoch, deactivation_epoch)| NativeDelegation {
+                stake,
+                activation_epoch,
+                deactivation_epoch,
+                ..NativeDelegation::default()
+            })
+            .collect();
+        let ours: Vec<Delegation> = delegations
+            .iter()
+            .map(|&(stake, activation_epoch, deactivation_epoch)| {
+                let mut delegation =
+                    Delegation::new(&Pubkey::default(), stake, activation_epoch.into());
+                delegation.set_deactivation_epoch(deactivation_epoch);
+                delegation
+            })
+            .collect();
+
+        let mut native_history = NativeStakeHistory::default();
+        let mut history = StakeHistory::default();
+
+        for epoch in 0..EPOCHS {
+            let mut cluster = NativeEntry::default();
+
+            for (native, ours) in native_delegations.iter().zip(&ours) {
+                let expected = native.stake_activating_and_deactivating(
+                    epoch,
+                    &native_history,
+                    new_rate_activation_epoch,
+                );
+                let actual = ours.stake_activating_and_deactivating(
+                    epoch.into(),
+                    &history,
+                    new_rate_activation_epoch.map(Epoch::from),
+                );
+                assert_eq!(actual, entry(&expected), "{native:?} at epoch {epoch}");
+
+                cluster.effective += expected.effective;
+                cluster.activating += expected.activating;
+                cluster.deactivating += expected.deactivating;
+            }
+
+            history.add(epoch, entry(&cluster));
+            native_history.add(epoch, cluster);
+        }
+    }
+
+    #[test]
+    fn test_stake_activating_and_deactivating_matches_native() {
+        // bootstrap stake keeps the cluster effective stake non-zero
+        let bootstrap = (1_000_000_000_000_000, u64::MAX, u64::MAX);
+
+        let cases: [&[(u64, u64, u64)]; 3] = [
+            &[bootstrap, (300_000_000_000, 1, 6)],
+            &[
+                bootstrap,
+                (333_333_333_333_333, 2, 9),
+                (777_777_777, 2, 3),
+                (1, 4, 5),
+                (123_456_789_012_345, 5, u64::MAX),
+                (999_999_999_999_999, 10, 12),
+            ],
+            &[
+                (3, u64::MAX, u64::MAX),
+                (17_000_000_000_000_003, 0, 20),
+                (4_000_000_000_000_007, 3, 25),
+                (1_000_000_000_000_001, 8, 9),
+            ],
+        ];
+
+        for delegations in cases {
+            for new_rate_activation_epoch in [None, Some(0), Some(7)] {
+                check_against_native(delegations, new_rate_activation_epoch);
+            }
+        }
+    }
+
+    // Property tests: random delegations over random stake history timelines,
+    // checked against invariants and against `Model`
+
+    use crate::state::stake_history::MAX_ENTRIES;
+    use proptest::{collection, option, prelude::*};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// Amounts stay below 2^59, about the SOL supply, so that the cluster sums
+    /// of at most 8 delegations fit in a u64
+    const MAX_AMOUNT_BITS: u32 = 59;
+
+    /// (effective, activating, deactivating)
+    type Status = (u64, u64, u64);
+
+    #[derive(Clone, Copy, Debug)]
+    struct Params {
+        stake: u64,
+        activation_epoch: u64,
+        deactivation_epoch: u64,
+    }
+
+    impl Params {
+        fn is_bootstrap(&self) -> bool {
+            self.activation_epoch == u64::MAX
+        }
+
+        fn delegation(&self) -> Delegation {
+            let mut delegation =
+                Delegation::new(&Pubkey::default(), self.stake, self.activation_epoch.into());
+            delegation.set_deactivation_epoch(self.deactivation_epoch);
+            delegation
+        }
+    }
+
+    /// Reference for `stake_activating_and_deactivating`, a transcription of the
+    /// native `f64` loop. It keeps the history the way `StakeHistory` does, the
+    /// newest `MAX_ENTRIES` entries.
+    #[derive(Default)]
+    struct Model {
+        history: BTreeMap<u64, Status>,
+        new_rate_activation_epoch: Option<u64>,
+    }
+
+    impl Model {
+        fn add(&mut self, epoch: u64, entry: Status) {
+            self.history.insert(epoch, entry);
+            while self.history.len() > MAX_ENTRIES {
+                self.history.pop_first();
+            }
+        }
+
+        /// `stake / cluster_stake * cluster_effective * rate`, at least 1, in
+        /// native's order of `f64` operations
+        fn change(
+            &self,
+            stake: u64,
+            cluster_stake: u64,
+            cluster_effective: u64,
+            epoch: u64,
+        ) -> u64 {
+            let rate = if epoch < self.new_rate_activation_epoch.unwrap_or(u64::MAX) {
+                0.25
+            } else {
+                0.09
+            };
+            let weight = stake as f64 / cluster_stake as f64;
+            let newly_effective_cluster_stake = cluster_effective as f64 * rate;
+
+            ((weight * newly_effective_cluster_stake) as u64).max(1)
+        }
+
+        /// (effective, activating) at `target_epoch`, before any deactivation
+        fn activate(&self, params: &Params, target_epoch: u64) -> (u64, u64) {
+            let stake = params.stake;
+            let activation_epoch = params.activation_epoch;
+
+            if params.is_bootstrap() {
+                return (stake, 0);
+            }
+            if activation_epoch == params.deactivation_epoch || target_epoch < activation_epoch {
+                return (0, 0);
+            }
+            if target_epoch == activation_epoch {
+                return (0, stake);
+            }
+            let Some(&(mut cluster)) = self.history.get(&activation_epoch) else {
+                // dropped out of history
+                return (stake, 0);
+            };
+
+            let mut effective = 0;
+            let mut epoch = activation_epoch;
+            while cluster.1 != 0 {
+                epoch += 1;
+                effective += self.change(stake - effective, cluster.1, cluster.0, epoch);
+                if effective >= stake {
+                    return (stake, 0);
+                }
+                if epoch >= target_epoch || epoch >= params.deactivation_epoch {
+                    break;
+                }
+                match self.history.get(&epoch) {
+                    Some(&next) => cluster = next,
+                    None => break,
+                }
+            }
+            (effective, stake - effective)
+        }
+
+        fn status(&self, params: &Params, target_epoch: u64) -> Status {
+            let (mut effective, activating) = self.activate(params, target_epoch);
+            let deactivation_epoch = params.deactivation_epoch;
+
+            if target_epoch < deactivation_epoch {
+                return (effective, activating, 0);
+            }
+            if target_epoch == deactivation_epoch {
+                return (effective, 0, effective);
+            }
+            let Some(&(mut cluster)) = self.history.get(&deactivation_epoch) else {
+                return (0, 0, 0);
+            };
+
+            let mut epoch = deactivation_epoch;
+            while cluster.2 != 0 {
+                epoch += 1;
+                let change = self.change(effective, cluster.2, cluster.0, epoch);
+                effective = effective.saturating_sub(change);
+                if effective == 0 || epoch >= target_epoch {
+                    break;
+                }
+                match self.history.get(&epoch) {
+                    Some(&next) => cluster = next,
+                    None => break,
+                }
+            }
+            (effective, 0, effective)
+        }
+    }
+
+    /// Random delegations and the timeline of the cluster they make up
+    #[derive(Debug)]
+    struct Timeline {
+        delegations: Vec<Params>,
+        epochs: u64,
+        /// epochs the history has no entry for
+        gaps: BTreeSet<u64>,
+        new_rate_activation_epoch: Option<u64>,
+    }
+
+    /// Uniform over bit lengths up to `MAX_AMOUNT_BITS`
+    fn amount() -> impl Strategy<Value = u64> {
+        (0..=MAX_AMOUNT_BITS, any::<u64>()).prop_map(|(bits, random)| match bits {
+            0 => 0,
+            bits => random >> (64 - bits),
+        })
+    }
+
+    fn bootstrap() -> impl Strategy<Value = Params> {
+        // warming up against a tiny effective stake takes hundreds of epochs
+        prop_oneof![1 => 1..=1_000u64, 2 => amount()].prop_map(|stake| Params {
+            stake,
+            activation_epoch: u64::MAX,
+            deactivation_epoch: u64::MAX,
+        })
+    }
+
+    fn delegation(epochs: u64) -> impl Strategy<Value = Params> {
+        let activation_epoch = prop_oneof![1 => Just(u64::MAX), 7 => 0..=epochs / 2];
+        (activation_epoch, 0..4u8, 0..epochs, amount()).prop_map(
+            move |(activation_epoch, kind, epoch, stake)| {
+                let deactivation_epoch = match kind {
+                    0 => u64::MAX,
+                    // instantly deactivated, or a deactivated bootstrap stake
+                    1 if activation_epoch == u64::MAX => epoch,
+                    1 => activation_epoch,
+                    _ => activation_epoch.saturating_add(1 + epoch),
+                };
+                Params {
+                    stake,
+                    activation_epoch,
+                    deactivation_epoch,
+                }
+            },
+        )
+    }
+
+    fn timeline() -> impl Strategy<Value = Timeline> {
+        // long enough for deactivations to drop out of history
+        let long = MAX_ENTRIES as u64 + 8..MAX_ENTRIES as u64 + 168;
+        prop_oneof![5 => 4..64u64, 1 => long]
+            .prop_flat_map(|epochs| {
+                let long = epochs > MAX_ENTRIES as u64;
+                let gaps = prop_oneof![
+                    3 => Just(BTreeSet::new()),
+                    1 => collection::btree_set(0..epochs, 0..=epochs as usize / 8),
+                ];
+                let new_rate_activation_epoch =
+                    prop_oneof![Just(None), Just(Some(0)), (0..epochs).prop_map(Some)];
+                (
+                    Just(epochs),
+                    option::weighted(0.875, bootstrap()),
+                    collection::vec(delegation(epochs), 1..=if long { 5 } else { 8 }),
+                    gaps,
+                    new_rate_activation_epoch,
+                )
+            })
+            .prop_map(
+                |(epochs, bootstrap, delegations, gaps, new_rate_activation_epoch)| Timeline {
+                    delegations: bootstrap.into_iter().chain(delegations).collect(),
+                    epochs,
+                    gaps,
+                    new_rate_activation_epoch,
+                },
+            )
+    }
+
+    /// Epochs until `stake` is fully cooled down with an unbroken history. The
+    /// delegation is part of the cluster's deactivating stake, so every epoch
+    /// takes at least 9% of what is left, and at least a lamport. 8% leaves
+    /// room for the `f64` rounding.
+    fn cooldown_epochs(stake: u64) -> u64 {
+        let mut left = stake;
+        let mut epochs = 0;
+        while left > 0 {
+            left -= (left * 8 / 100).max(1);
+            epochs += 1;
+        }
+        epochs
+    }
+
+    fn check_timeline(timeline: &Timeline) {
+        let new_rate_activation_epoch = timeline.new_rate_activation_epoch.map(Epoch::from);
+
+        let mut model = Model {
+            new_rate_activation_epoch: timeline.new_rate_activation_epoch,
+            ..Model::default()
+        };
+        let mut history = StakeHistory::default();
+        let delegations: Vec<Delegation> = timeline
+            .delegations
+            .iter()
+            .map(Params::delegation)
+            .collect();
+        let mut previous = vec![StakeActivationStatus::default(); delegations.len()];
+
+        for epoch in 0..timeline.epochs {
+            let mut cluster = (0, 0, 0);
+
+            for (i, (params, delegation)) in
+                timeline.delegations.iter().zip(&delegations).enumerate()
+            {
+                let context = || format!("{params:?} at epoch {epoch}");
+                let status = delegation.stake_activating_and_deactivating(
+                    epoch.into(),
+                    &history,
+                    new_rate_activation_epoch,
+                );
+                let (effective, activating, deactivating) = (
+                    status.effective.get(),
+                    status.activating.get(),
+                    status.deactivating.get(),
+                );
+
+                assert!(effective + activating <= params.stake, "{}", context());
+                assert!(deactivating <= effective, "{}", context());
+                if epoch < params.deactivation_epoch {
+                    assert_eq!(deactivating, 0, "{}", context());
+                    // warmup never goes back
+                    assert!(effective >= previous[i].effective.get(), "{}", context());
+                } else {
+                    assert_eq!(activating, 0, "{}", context());
+                    // nor does cooldown, until the activation drops out of history and
+                    // native takes the stake for fully activated again
+                    let activation_in_history = params.is_bootstrap()
+                        || model.history.contains_key(&params.activation_epoch);
+                    if epoch > params.deactivation_epoch && activation_in_history {
+                        assert!(effective <= previous[i].effective.get(), "{}", context());
+                    }
+                    if timeline.gaps.is_empty()
+                        && activation_in_history
+                        && epoch
+                            == params.deactivation_epoch + cooldown_epochs(params.stake).max(1)
+                    {
+                        // fully cooled down while the deactivation is still in history
+                        assert!(
+                            model.history.contains_key(&params.deactivation_epoch),
+                            "{}",
+                            context()
+                        );
+                        assert_eq!(status, StakeActivationStatus::default(), "{}", context());
+                    }
+                    if !model.history.contains_key(&params.deactivation_epoch)
+                        && epoch > params.deactivation_epoch
+                    {
+                        // and in any case once the deactivation is out of history
+                        assert_eq!(status, StakeActivationStatus::default(), "{}", context());
+                    }
+                }
+
+                assert_eq!(
+                    (effective, activating, deactivating),
+                    model.status(params, epoch),
+                    "{}",
+                    context()
+                );
+
+                // the history records what the program computed, like a validator
+                cluster.0 += effective;
+                cluster.1 += activating;
+                cluster.2 += deactivating;
+                previous[i] = status;
+            }
+
+            if !timeline.gaps.contains(&epoch) {
+                model.add(epoch, cluster);
+                history.add(
+                    epoch,
+                    StakeHistoryEntry {
+                        effective: cluster.0.into(),
+                        activating: cluster.1.into(),
+                        deactivating: cluster.2.into(),
+                    },
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(300))]
+
+        #[test]
+        fn test_stake_activating_and_deactivating_properties(timeline in timeline()) {
+            check_timeline(&timeline);
+        }
+    }
+
+    #[test]
+    fn test_cooldown_epochs() {
+        // any delegation cools down before its deactivation leaves the history
+        assert!(cooldown_epochs(u64::MAX >> (64 - MAX_AMOUNT_BITS)) < MAX_ENTRIES as u64);
+        assert_eq!(cooldown_epochs(0), 0);
+        assert_eq!(cooldown_epochs(12), 12);
+    }
+
+    #[test]
+    fn test_model_matches_native() {
+        let stake = 123_456_789_012_345;
+        let mut model = Model::default();
+        let mut native_history = NativeStakeHistory::default();
+        let params = Params {
+            stake,
+            activation_epoch: 1,
+            deactivation_epoch: 6,
+        };
+        let native = NativeDelegation {
+            stake,
+            activation_epoch: 1,
+            deactivation_epoch: 6,
+            ..NativeDelegation::default()
+        };
+
+        for epoch in 0..12 {
+            let expected = native.stake_activating_and_deactivating(epoch, &native_history, None);
+            assert_eq!(
+                model.status(&params, epoch),
+                (expected.effective, expected.activating, expected.deactivating),
+                "at epoch {epoch}"
+            );
+
+            // a bootstrap stake of 10x keeps the cluster effective
+            let entry = NativeEntry {
+                effective: expected.effective + 10 * stake,
+                activating: expected.activating,
+                deactivating: expected.deactivating,
+            };
+            model.add(
+                epoch,
+                (entry.effective, entry.activating, entry.deactivating),
+            );
+            native_history.add(epoch, entry);
+        }
+    }
+}
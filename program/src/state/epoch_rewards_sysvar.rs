@@ -0,0 +1,78 @@
+//! Status of partitioned epoch rewards distribution.
+//!
+//! Only the `active` flag of the [`EpochRewards`] sysvar is needed by the stake
+//! program, so it is read in place through `sol_get_sysvar` rather than
+//! deserializing the whole sysvar.
+//!
+//! [`EpochRewards`]: https://docs.rs/solana-epoch-rewards/latest/solana_epoch_rewards/struct.EpochRewards.html
+
+use crate::state::get_sysvar;
+
+pub mod epoch_rewards_id {
+    pinocchio_pubkey::declare_id!("SysvarEpochRewards1111111111111111111111111");
+}
+
+/// Offset of `active` in the bincode `EpochRewards`, after
+/// `distribution_starting_block_height`, `num_partitions`, `parent_blockhash`,
+/// `total_points`, `total_rewards` and `distributed_rewards`
+const ACTIVE_OFFSET: u64 = 8 + 8 + 32 + 16 + 8 + 8;
+
+pub struct EpochRewardsSysvar;
+
+impl EpochRewardsSysvar {
+    /// Whether rewards are being distributed, during which stake accounts must
+    /// not be modified. Like native, an unavailable sysvar reads as inactive.
+    pub fn is_active() -> bool {
+        let mut active = [0; 1];
+
+        get_sysvar(&mut active, &epoch_rewards_id::ID, ACTIVE_OFFSET, 1)
+            .map(|()| active[0] == 1)
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::epoch_rewards::EpochRewards;
+
+    use super::*;
+    use crate::state::{with_syscall_stubs, SysvarStubs};
+
+    fn with_epoch_rewards<R>(epoch_rewards: &EpochRewards, f: impl FnOnce() -> R) -> R {
+        let stubs = SysvarStubs::default().with_sysvar(&epoch_rewards_id::ID, epoch_rewards);
+        with_syscall_stubs(stubs, f)
+    }
+
+    #[test]
+    fn test_id() {
+        assert_eq!(
+            epoch_rewards_id::ID,
+            solana_sdk::sysvar::epoch_rewards::ID.to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_is_active() {
+        // every other field is set so a wrong offset would read one of them
+        let mut epoch_rewards = EpochRewards {
+            distribution_starting_block_height: u64::MAX,
+            num_partitions: u64::MAX,
+            parent_blockhash: [0xff; 32].into(),
+            total_points: u128::MAX,
+            total_rewards: u64::MAX,
+            distributed_rewards: u64::MAX,
+            active: false,
+        };
+        with_epoch_rewards(&epoch_rewards, || assert!(!EpochRewardsSysvar::is_active()));
+
+        epoch_rewards.active = true;
+        with_epoch_rewards(&epoch_rewards, || assert!(EpochRewardsSysvar::is_active()));
+    }
+
+    #[test]
+    fn test_is_active_unavailable() {
+        with_syscall_stubs(SysvarStubs::default(), || {
+            assert!(!EpochRewardsSysvar::is_active())
+        });
+    }
+}
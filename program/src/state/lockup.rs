@@ -1,13 +1,13 @@
 use pinocchio::{pubkey::Pubkey, sysvars::clock::Clock};
 
-use super::Epoch;
+use super::{Epoch, UnixTimestamp};
 
 #[repr(C)]
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Lockup {
     /// UnixTimestamp at which this stake will allow withdrawal, unless the
     ///   transaction is signed by the custodian
-    pub unix_timestamp: [u8; 8], //i64
+    pub unix_timestamp: UnixTimestamp,
     /// epoch height at which this stake will allow withdrawal, unless the
     ///   transaction is signed by the custodian
     pub epoch: Epoch,
@@ -19,29 +19,71 @@ pub struct Lockup {
 impl Lockup {
     #[inline(always)]
     pub fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
-        self.unix_timestamp = unix_timestamp.to_le_bytes();
+        self.unix_timestamp = unix_timestamp.into();
     }
 
     #[inline(always)]
     pub fn unix_timestamp(&self) -> i64 {
-        i64::from_le_bytes(self.unix_timestamp)
+        self.unix_timestamp.get()
     }
 
     #[inline(always)]
     pub fn set_epoch(&mut self, epoch: u64) {
-        self.epoch = epoch.to_le_bytes();
+        self.epoch = epoch.into();
     }
 
     #[inline(always)]
     pub fn epoch(&self) -> u64 {
-        u64::from_le_bytes(self.epoch)
+        self.epoch.get()
     }
 
     pub fn is_in_force(&self, clock: &Clock, custodian: Option<&Pubkey>) -> bool {
         if custodian == Some(&self.custodian) {
             return false;
         }
-        self.unix_timestamp > clock.unix_timestamp.to_le_bytes()
-            || self.epoch > clock.epoch.to_le_bytes()
+        self.unix_timestamp() > clock.unix_timestamp || self.epoch() > clock.epoch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lockup;
+    use pinocchio::{pubkey::Pubkey, sysvars::clock::Clock};
+
+    fn clock(unix_timestamp: i64, epoch: u64) -> Clock {
+        Clock {
+            unix_timestamp,
+            epoch,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_is_in_force() {
+        let custodian = [7; 32];
+        let mut lockup = Lockup {
+            custodian,
+            ..Lockup::default()
+        };
+        lockup.set_unix_timestamp(256);
+        lockup.set_epoch(256);
+
+        // compared as numbers, not as little-endian byte strings
+        assert!(lockup.is_in_force(&clock(255, 0), None));
+        assert!(lockup.is_in_force(&clock(0, 255), None));
+        assert!(!lockup.is_in_force(&clock(256, 256), None));
+        assert!(!lockup.is_in_force(&clock(257, 511), None));
+
+        assert!(!lockup.is_in_force(&clock(0, 0), Some(&custodian)));
+        assert!(lockup.is_in_force(&clock(0, 0), Some(&Pubkey::default())));
+    }
+
+    #[test]
+    fn test_is_in_force_negative_timestamp() {
+        let mut lockup = Lockup::default();
+        lockup.set_unix_timestamp(-1);
+
+        assert!(!lockup.is_in_force(&clock(0, 0), None));
+        assert!(lockup.is_in_force(&clock(-2, 0), None));
     }
 }
@@ -0,0 +1,434 @@
+use pinocchio::{program_error::ProgramError, sysvars::clock::Clock, ProgramResult};
+
+use crate::{consts::PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH, error::StakeError};
+
+use super::{
+    checked_add, Delegation, Meta, Stake, StakeFlags, StakeHistoryGetEntry,
+    StakeStateV2,
+};
+
+/// Mergeable shape of a stake account, mirroring the native `MergeKind`.
+///
+/// Accounts with transient stake (effective stake that is still activating or
+/// deactivating) have no kind and cannot be merged.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MergeKind {
+    Inactive(Meta, u64, StakeFlags),
+    ActivationEpoch(Meta, Stake, StakeFlags),
+    FullyActive(Meta, Stake),
+}
+
+impl MergeKind {
+    pub fn meta(&self) -> &Meta {
+        match self {
+            Self::Inactive(meta, _, _) => meta,
+            Self::ActivationEpoch(meta, _, _) => meta,
+            Self::FullyActive(meta, _) => meta,
+        }
+    }
+
+    pub fn active_stake(&self) -> Option<&Stake> {
+        match self {
+            Self::Inactive(_, _, _) => None,
+            Self::ActivationEpoch(_, stake, _) => Some(stake),
+            Self::FullyActive(_, stake) => Some(stake),
+        }
+    }
+
+    pub fn get_if_mergeable<T: StakeHistoryGetEntry>(
+        stake_state: &StakeStateV2,
+        stake_lamports: u64,
+        clock: &Clock,
+        stake_history: &T,
+    ) -> Result<Self, ProgramError> {
+        match stake_state {
+            StakeStateV2::Stake(meta, stake, stake_flags) => {
+                // stake must not be in a transient state. Transient here meaning
+                // activating or deactivating with non-zero effective stake.
+                let status = stake.delegation.stake_activating_and_deactivating(
+                    clock.epoch.into(),
+                    stake_history,
+                    PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+                );
+
+                match (
+                    status.effective.get(),
+                    status.activating.get(),
+                    status.deactivating.get(),
+                ) {
+                    (0, 0, 0) => Ok(Self::Inactive(*meta, stake_lamports, *stake_flags)),
+                    (0, _, _) => Ok(Self::ActivationEpoch(*meta, *stake, *stake_flags)),
+                    (_, 0, 0) => Ok(Self::FullyActive(*meta, *stake)),
+                    _ => Err(StakeError::MergeTransientStake.into()),
+                }
+            }
+            StakeStateV2::Initialized(meta) => {
+                Ok(Self::Inactive(*meta, stake_lamports, StakeFlags::empty()))
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    pub fn metas_can_merge(stake: &Meta, source: &Meta, clock: &Clock) -> ProgramResult {
+        // lockups may mismatch so long as both have expired
+        let can_merge_lockups = stake.lockup == source.lockup
+            || (!stake.lockup.is_in_force(clock, None) && !source.lockup.is_in_force(clock, None));
+        // `rent_exempt_reserve` has no bearing on the mergeability of accounts,
+        // as the source account will be culled by runtime once the operation
+        // succeeds. Considering it here would needlessly prevent merging stake
+        // accounts with differing data lengths, which already exist in the wild
+        // due to an SDK bug
+        if stake.authorized == source.authorized && can_merge_lockups {
+            Ok(())
+        } else {
+            Err(StakeError::MergeMismatch.into())
+        }
+    }
+
+    pub fn active_delegations_can_merge(stake: &Delegation, source: &Delegation) -> ProgramResult {
+        if stake.voter_pubkey != source.voter_pubkey {
+            Err(StakeError::MergeMismatch.into())
+        } else if stake.deactivation_epoch() == u64::MAX && source.deactivation_epoch() == u64::MAX
+        {
+            Ok(())
+        } else {
+            Err(StakeError::MergeMismatch.into())
+        }
+    }
+
+    /// Folds `source` into `self`, returning the new destination state, or
+    /// `None` when the destination state is left as is
+    pub fn merge(self, source: Self, clock: &Clock) -> Result<Option<StakeStateV2>, ProgramError> {
+        Self::metas_can_merge(self.meta(), source.meta(), clock)?;
+        self.active_stake()
+            .zip(source.active_stake())
+            .map(|(stake, source)| {
+                Self::active_delegations_can_merge(&stake.delegation, &source.delegation)
+            })
+            .unwrap_or(Ok(()))?;
+
+        let merged_state = match (self, source) {
+            (Self::Inactive(_, _, _), Self::Inactive(_, _, _)) => None,
+            (Self::Inactive(_, _, _), Self::ActivationEpoch(_, _, _)) => None,
+            (
+                Self::ActivationEpoch(meta, mut stake, stake_flags),
+                Self::Inactive(_, source_lamports, source_stake_flags),
+            ) => {
+                stake.delegation.set_stake(checked_add(
+                    stake.delegation.stake.get(),
+                    source_lamports,
+                )?);
+                Some(StakeStateV2::Stake(
+                    meta,
+                    stake,
+                    stake_flags.union(source_stake_flags),
+                ))
+            }
+            (
+                Self::ActivationEpoch(meta, mut stake, stake_flags),
+                Self::ActivationEpoch(source_meta, source_stake, source_stake_flags),
+            ) => {
+                let source_lamports = checked_add(
+                    source_meta.rent_exempt_reserve(),
+                    source_stake.delegation.stake.get(),
+                )?;
+                merge_delegation_stake_and_credits_observed(
+                    &mut stake,
+                    source_lamports,
+                    source_stake.credits_observed(),
+                )?;
+                Some(StakeStateV2::Stake(
+                    meta,
+                    stake,
+                    stake_flags.union(source_stake_flags),
+                ))
+            }
+            (Self::FullyActive(meta, mut stake), Self::FullyActive(_, source_stake)) => {
+                // Don't stake the source account's `rent_exempt_reserve` to
+                // protect against the magic activation loophole. It will
+                // instead be moved into the destination account as extra,
+                // withdrawable `lamports`
+                merge_delegation_stake_and_credits_observed(
+                    &mut stake,
+                    source_stake.delegation.stake.get(),
+                    source_stake.credits_observed(),
+                )?;
+                Some(StakeStateV2::Stake(meta, stake, StakeFlags::empty()))
+            }
+            _ => return Err(StakeError::MergeMismatch.into()),
+        };
+
+        Ok(merged_state)
+    }
+}
+
+pub fn merge_delegation_stake_and_credits_observed(
+    stake: &mut Stake,
+    absorbed_lamports: u64,
+    absorbed_credits_observed: u64,
+) -> ProgramResult {
+    let credits_observed =
+        stake_weighted_credits_observed(stake, absorbed_lamports, absorbed_credits_observed)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    stake.set_credits_observed(credits_observed);
+    stake.delegation.set_stake(checked_add(
+        stake.delegation.stake.get(),
+        absorbed_lamports,
+    )?);
+
+    Ok(())
+}
+
+/// Credits observed of the merged stake, weighted by the lamports of each side
+/// and rounded up
+pub fn stake_weighted_credits_observed(
+    stake: &Stake,
+    absorbed_lamports: u64,
+    absorbed_credits_observed: u64,
+) -> Option<u64> {
+    if stake.credits_observed() == absorbed_credits_observed {
+        Some(stake.credits_observed())
+    } else {
+        let stake_lamports = stake.delegation.stake.get();
+        let total_stake = u128::from(stake_lamports.checked_add(absorbed_lamports)?);
+        let stake_weighted_credits =
+            u128::from(stake.credits_observed()).checked_mul(u128::from(stake_lamports))?;
+        let absorbed_weighted_credits =
+            u128::from(absorbed_credits_observed).checked_mul(u128::from(absorbed_lamports))?;
+        // Discard fractional credits as a merge side-effect friction by taking
+        // the ceiling, done by adding `denominator - 1` to the numerator.
+        let total_weighted_credits = stake_weighted_credits
+            .checked_add(absorbed_weighted_credits)?
+            .checked_add(total_stake)?
+            .checked_sub(1)?;
+        u64::try_from(total_weighted_credits.checked_div(total_stake)?).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Authorized, Lockup, StakeHistory};
+    use pinocchio::pubkey::Pubkey;
+
+    fn meta() -> Meta {
+        Meta::auto(&[1; 32])
+    }
+
+    fn stake(voter_pubkey: &Pubkey, lamports: u64, activation_epoch: u64) -> Stake {
+        let mut stake = Stake::default();
+        stake.delegation = Delegation::new(voter_pubkey, lamports, activation_epoch.into());
+        stake
+    }
+
+    fn clock(epoch: u64) -> Clock {
+        Clock {
+            epoch,
+            ..Clock::default()
+        }
+    }
+
+    #[test]
+    fn test_stake_weighted_credits_observed() {
+        let mut stake = stake(&[2; 32], 100, 0);
+        stake.set_credits_observed(10);
+
+        // equal credits are kept as is
+        assert_eq!(stake_weighted_credits_observed(&stake, 1, 10), Some(10));
+        // (10 * 100 + 20 * 100) / 200
+        assert_eq!(stake_weighted_credits_observed(&stake, 100, 20), Some(15));
+        // (10 * 100 + 11 * 1) / 101 = 10.0099.., rounded up
+        assert_eq!(stake_weighted_credits_observed(&stake, 1, 11), Some(11));
+        assert_eq!(stake_weighted_credits_observed(&stake, u64::MAX, 11), None);
+
+        stake.delegation.set_stake(u64::MAX / 2);
+        stake.set_credits_observed(u64::MAX);
+        assert_eq!(
+            stake_weighted_credits_observed(&stake, u64::MAX / 2, u64::MAX - 1),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_get_if_mergeable() {
+        let history = StakeHistory::default();
+        let meta = meta();
+        let stake = stake(&[2; 32], 100, 5);
+
+        assert_eq!(
+            MergeKind::get_if_mergeable(&StakeStateV2::Initialized(meta), 42, &clock(5), &history),
+            Ok(MergeKind::Inactive(meta, 42, StakeFlags::empty()))
+        );
+
+        let state = StakeStateV2::Stake(meta, stake, StakeFlags::empty());
+        assert_eq!(
+            MergeKind::get_if_mergeable(&state, 42, &clock(4), &history),
+            Ok(MergeKind::Inactive(meta, 42, StakeFlags::empty()))
+        );
+        assert_eq!(
+            MergeKind::get_if_mergeable(&state, 42, &clock(5), &history),
+            Ok(MergeKind::ActivationEpoch(meta, stake, StakeFlags::empty()))
+        );
+        // without history the stake is assumed fully effective
+        assert_eq!(
+            MergeKind::get_if_mergeable(&state, 42, &clock(6), &history),
+            Ok(MergeKind::FullyActive(meta, stake))
+        );
+
+        let mut deactivating = stake;
+        deactivating.delegation.set_deactivation_epoch(6);
+        assert_eq!(
+            MergeKind::get_if_mergeable(
+                &StakeStateV2::Stake(meta, deactivating, StakeFlags::empty()),
+                42,
+                &clock(6),
+                &history
+            ),
+            Err(StakeError::MergeTransientStake.into())
+        );
+
+        for state in [StakeStateV2::Uninitialized, StakeStateV2::RewardsPool] {
+            assert_eq!(
+                MergeKind::get_if_mergeable(&state, 42, &clock(6), &history),
+                Err(ProgramError::InvalidAccountData)
+            );
+        }
+    }
+
+    #[test]
+    fn test_metas_can_merge() {
+        let clock = clock(10);
+        let meta = meta();
+
+        // rent exempt reserve is ignored
+        let mut other = meta;
+        other.set_rent_exempt_reserve(1);
+        assert_eq!(MergeKind::metas_can_merge(&meta, &other, &clock), Ok(()));
+
+        let mut other = meta;
+        other.authorized = Authorized::auto(&[3; 32]);
+        assert_eq!(
+            MergeKind::metas_can_merge(&meta, &other, &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+
+        // expired lockups may differ
+        let mut other = meta;
+        other.lockup.set_epoch(10);
+        assert_eq!(MergeKind::metas_can_merge(&meta, &other, &clock), Ok(()));
+
+        // identical lockups may be in force
+        let mut in_force = meta;
+        in_force.lockup.set_epoch(11);
+        assert_eq!(
+            MergeKind::metas_can_merge(&in_force, &in_force, &clock),
+            Ok(())
+        );
+        assert_eq!(
+            MergeKind::metas_can_merge(&meta, &in_force, &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+        assert_eq!(
+            MergeKind::metas_can_merge(&in_force, &meta, &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+
+        let mut other = in_force;
+        other.lockup = Lockup {
+            custodian: [4; 32],
+            ..in_force.lockup
+        };
+        assert_eq!(
+            MergeKind::metas_can_merge(&in_force, &other, &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let clock = clock(10);
+        let meta = meta();
+        let flags = StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED;
+        let mut active = stake(&[2; 32], 100, 0);
+        active.set_credits_observed(10);
+        let mut activating = stake(&[2; 32], 100, 10);
+        activating.set_credits_observed(20);
+
+        let inactive = MergeKind::Inactive(meta, 50, flags);
+        assert_eq!(inactive.clone().merge(inactive.clone(), &clock), Ok(None));
+        assert_eq!(
+            inactive
+                .clone()
+                .merge(MergeKind::ActivationEpoch(meta, activating, flags), &clock),
+            Ok(None)
+        );
+
+        // inactive lamports are all staked and flags are combined
+        let mut expected = activating;
+        expected.delegation.set_stake(150);
+        assert_eq!(
+            MergeKind::ActivationEpoch(meta, activating, StakeFlags::empty())
+                .merge(inactive.clone(), &clock),
+            Ok(Some(StakeStateV2::Stake(meta, expected, flags)))
+        );
+
+        // the source reserve is staked as well
+        let mut source_meta = meta;
+        source_meta.set_rent_exempt_reserve(100);
+        let mut source = activating;
+        source.set_credits_observed(40);
+        expected.delegation.set_stake(300);
+        // (20 * 100 + 40 * 200) / 300 = 33.3.., rounded up
+        expected.set_credits_observed(34);
+        assert_eq!(
+            MergeKind::ActivationEpoch(meta, activating, StakeFlags::empty()).merge(
+                MergeKind::ActivationEpoch(source_meta, source, flags),
+                &clock
+            ),
+            Ok(Some(StakeStateV2::Stake(meta, expected, flags)))
+        );
+
+        // the source reserve is not staked and flags are dropped
+        let mut expected = active;
+        expected.delegation.set_stake(200);
+        expected.set_credits_observed(15);
+        let mut source = active;
+        source.set_credits_observed(20);
+        assert_eq!(
+            MergeKind::FullyActive(meta, active)
+                .merge(MergeKind::FullyActive(source_meta, source), &clock),
+            Ok(Some(StakeStateV2::Stake(
+                meta,
+                expected,
+                StakeFlags::empty()
+            )))
+        );
+
+        // mismatched kinds
+        assert_eq!(
+            MergeKind::FullyActive(meta, active).merge(inactive.clone(), &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+        assert_eq!(
+            inactive
+                .clone()
+                .merge(MergeKind::FullyActive(meta, active), &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+        assert_eq!(
+            MergeKind::ActivationEpoch(meta, activating, flags)
+                .merge(MergeKind::FullyActive(meta, active), &clock),
+            Err(StakeError::MergeMismatch.into())
+        );
+
+        // mismatched voters
+        assert_eq!(
+            MergeKind::FullyActive(meta, active).merge(
+                MergeKind::FullyActive(meta, stake(&[3; 32], 100, 0)),
+                &clock
+            ),
+            Err(StakeError::MergeMismatch.into())
+        );
+    }
+}
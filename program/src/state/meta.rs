@@ -1,8 +1,8 @@
 use pinocchio::{program_error::ProgramError, pubkey::Pubkey, sysvars::clock::Clock};
 
-use super::{Authorized, Epoch, Lockup};
+use super::{utils::DataLen, Authorized, Epoch, Lockup, PodI64, PodU64};
 
-pub type UnixTimestamp = [u8; 8];
+pub type UnixTimestamp = PodI64;
 
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
 pub struct LockupArgs {
@@ -11,23 +11,35 @@ pub struct LockupArgs {
     pub custodian: Option<Pubkey>,
 }
 
+/// Arguments of `SetLockupChecked`: the new custodian, if any, is taken from a
+/// signing account instead of the instruction data
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LockupCheckedArgs {
+    pub unix_timestamp: Option<UnixTimestamp>,
+    pub epoch: Option<Epoch>,
+}
+
 #[repr(C)]
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
 pub struct Meta {
-    pub rent_exempt_reserve: [u8; 8], // u64
+    pub rent_exempt_reserve: PodU64,
     pub authorized: Authorized,
     pub lockup: Lockup,
 }
 
+impl DataLen for Meta {
+    const LEN: usize = core::mem::size_of::<Meta>();
+}
+
 impl Meta {
     #[inline(always)]
     pub fn set_rent_exempt_reserve(&mut self, rent_exempt_reserve: u64) {
-        self.rent_exempt_reserve = rent_exempt_reserve.to_le_bytes();
+        self.rent_exempt_reserve = rent_exempt_reserve.into();
     }
 
     #[inline(always)]
     pub fn rent_exempt_reserve(&self) -> u64 {
-        u64::from_le_bytes(self.rent_exempt_reserve)
+        self.rent_exempt_reserve.get()
     }
 
     pub fn set_lockup(
@@ -1,7 +1,10 @@
 pub mod authorized;
 pub mod delegation;
+pub mod epoch_rewards_sysvar;
 pub mod lockup;
+pub mod merge;
 pub mod meta;
+pub mod pod;
 pub mod stake;
 pub mod stake_authorize;
 pub mod stake_flags;
@@ -9,14 +12,19 @@ pub mod stake_history;
 pub mod stake_history_sysvar;
 pub mod stake_state_v2;
 pub mod utils;
+pub mod vote_state;
+pub mod warmup_cooldown;
 pub mod redelegate_state;
 
 pub use authorized::*;
 pub use delegation::*;
+pub use epoch_rewards_sysvar::EpochRewardsSysvar;
 pub use lockup::*;
+pub use merge::*;
 pub use meta::*;
+pub use pod::*;
 use pinocchio::{
-    account_info::{AccountInfo, Ref},
+    account_info::AccountInfo,
     program_error::ProgramError,
     ProgramResult,
 };
@@ -27,19 +35,21 @@ pub use stake_history::*;
 pub use stake_history_sysvar::*;
 pub use stake_state_v2::*;
 pub use utils::*;
+pub use vote_state::*;
+pub use warmup_cooldown::*;
 pub use redelegate_state::*;
 
 
-pub type Epoch = [u8; 8]; //u64
+pub type Epoch = PodU64;
 
-pub fn get_stake_state(
-    stake_account_info: &AccountInfo,
-) -> Result<Ref<StakeStateV2>, ProgramError> {
-    if stake_account_info.is_owned_by(&crate::ID) {
+pub fn get_stake_state(stake_account_info: &AccountInfo) -> Result<StakeStateV2, ProgramError> {
+    if !stake_account_info.is_owned_by(&crate::ID) {
         return Err(ProgramError::InvalidAccountOwner);
     }
 
-    StakeStateV2::from_account_info(stake_account_info)
+    let data = stake_account_info.try_borrow_data()?;
+
+    StakeStateV2View::from_bytes(&data).map(|view| view.to_state())
 }
 
 /// # Safety
@@ -48,32 +58,21 @@ pub fn get_stake_state(
 /// no mutable borrows of the account data.
 pub unsafe fn get_stake_state_unchecked(
     stake_account_info: &AccountInfo,
-) -> Result<&StakeStateV2, ProgramError> {
+) -> Result<StakeStateV2View<'_>, ProgramError> {
     if stake_account_info.owner() != &crate::ID {
         return Err(ProgramError::InvalidAccountOwner);
     }
 
-    StakeStateV2::from_account_info_unchecked(stake_account_info)
+    StakeStateV2View::from_bytes(stake_account_info.borrow_data_unchecked())
 }
 
 pub fn set_stake_state(
-    _stake_account_info: &AccountInfo,
-    _new_state: &StakeStateV2,
+    stake_account_info: &AccountInfo,
+    new_state: &StakeStateV2,
 ) -> ProgramResult {
-    todo!()
-
-    /*
-
-    //--------------- Code to swap ------------
-     let serialized_size =
-        bincode::serialized_size(new_state).map_err(|_| ProgramError::InvalidAccountData)?;
-    if serialized_size > stake_account_info.data_len() as u64 {
-        return Err(ProgramError::AccountDataTooSmall);
-    }
-
     let mut data = stake_account_info.try_borrow_mut_data()?;
-    bincode::serialize_into(&mut data[..], new_state).map_err(|_| ProgramError::InvalidAccountData)
-     */
+
+    new_state.serialize_into(&mut data)
 }
 
 // dont call this "move" because we have an instruction MoveLamports
@@ -0,0 +1,229 @@
+//! Integers stored as little-endian bytes.
+//!
+//! Account state is read and written in place, so its fields must have an
+//! alignment of 1. These wrappers keep that layout while only ever converting
+//! through little-endian, so a field can't be decoded with the wrong byte order.
+
+use core::{cmp::Ordering, fmt};
+
+/// `u64` stored as little-endian bytes
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PodU64([u8; 8]);
+
+impl PodU64 {
+    pub const ZERO: Self = Self::from_u64(0);
+    pub const MAX: Self = Self::from_u64(u64::MAX);
+
+    #[inline(always)]
+    pub const fn from_u64(value: u64) -> Self {
+        Self(value.to_le_bytes())
+    }
+
+    #[inline(always)]
+    pub const fn from_le_bytes(bytes: [u8; 8]) -> Self {
+        Self(bytes)
+    }
+
+    #[inline(always)]
+    pub const fn get(self) -> u64 {
+        u64::from_le_bytes(self.0)
+    }
+
+    #[inline(always)]
+    pub const fn to_le_bytes(self) -> [u8; 8] {
+        self.0
+    }
+
+    #[inline(always)]
+    pub fn checked_add(self, rhs: u64) -> Option<Self> {
+        self.get().checked_add(rhs).map(Self::from_u64)
+    }
+
+    #[inline(always)]
+    pub fn checked_sub(self, rhs: u64) -> Option<Self> {
+        self.get().checked_sub(rhs).map(Self::from_u64)
+    }
+
+    #[inline(always)]
+    pub fn saturating_add(self, rhs: u64) -> Self {
+        Self::from_u64(self.get().saturating_add(rhs))
+    }
+
+    #[inline(always)]
+    pub fn saturating_sub(self, rhs: u64) -> Self {
+        Self::from_u64(self.get().saturating_sub(rhs))
+    }
+}
+
+impl From<u64> for PodU64 {
+    #[inline(always)]
+    fn from(value: u64) -> Self {
+        Self::from_u64(value)
+    }
+}
+
+impl From<PodU64> for u64 {
+    #[inline(always)]
+    fn from(value: PodU64) -> Self {
+        value.get()
+    }
+}
+
+impl PartialEq<u64> for PodU64 {
+    #[inline(always)]
+    fn eq(&self, other: &u64) -> bool {
+        self.get() == *other
+    }
+}
+
+impl PartialOrd for PodU64 {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// numeric order, comparing the bytes would be wrong for little-endian
+impl Ord for PodU64 {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+impl fmt::Debug for PodU64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.get(), f)
+    }
+}
+
+/// `i64` stored as little-endian bytes
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct PodI64([u8; 8]);
+
+impl PodI64 {
+    pub const ZERO: Self = Self::from_i64(0);
+
+    #[inline(always)]
+    pub const fn from_i64(value: i64) -> Self {
+        Self(value.to_le_bytes())
+    }
+
+    #[inline(always)]
+    pub const fn from_le_bytes(bytes: [u8; 8]) -> Self {
+        Self(bytes)
+    }
+
+    #[inline(always)]
+    pub const fn get(self) -> i64 {
+        i64::from_le_bytes(self.0)
+    }
+
+    #[inline(always)]
+    pub const fn to_le_bytes(self) -> [u8; 8] {
+        self.0
+    }
+
+    #[inline(always)]
+    pub fn checked_add(self, rhs: i64) -> Option<Self> {
+        self.get().checked_add(rhs).map(Self::from_i64)
+    }
+
+    #[inline(always)]
+    pub fn checked_sub(self, rhs: i64) -> Option<Self> {
+        self.get().checked_sub(rhs).map(Self::from_i64)
+    }
+
+    #[inline(always)]
+    pub fn saturating_add(self, rhs: i64) -> Self {
+        Self::from_i64(self.get().saturating_add(rhs))
+    }
+
+    #[inline(always)]
+    pub fn saturating_sub(self, rhs: i64) -> Self {
+        Self::from_i64(self.get().saturating_sub(rhs))
+    }
+}
+
+impl From<i64> for PodI64 {
+    #[inline(always)]
+    fn from(value: i64) -> Self {
+        Self::from_i64(value)
+    }
+}
+
+impl From<PodI64> for i64 {
+    #[inline(always)]
+    fn from(value: PodI64) -> Self {
+        value.get()
+    }
+}
+
+impl PartialEq<i64> for PodI64 {
+    #[inline(always)]
+    fn eq(&self, other: &i64) -> bool {
+        self.get() == *other
+    }
+}
+
+impl PartialOrd for PodI64 {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PodI64 {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+impl fmt::Debug for PodI64 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.get(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_little_endian_layout() {
+        assert_eq!(core::mem::size_of::<PodU64>(), 8);
+        assert_eq!(core::mem::align_of::<PodU64>(), 1);
+        assert_eq!(core::mem::align_of::<PodI64>(), 1);
+
+        assert_eq!(PodU64::from(0x0102).to_le_bytes(), [2, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(
+            PodI64::from(-2).to_le_bytes(),
+            [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_numeric_order() {
+        // 256 is [0, 1, ..] and 255 is [255, 0, ..] in little-endian
+        assert!(PodU64::from(256) > PodU64::from(255));
+        assert!(PodU64::MAX > PodU64::ZERO);
+        assert!(PodI64::from(-1) < PodI64::ZERO);
+        assert!(PodI64::from(256) > PodI64::from(255));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(PodU64::from(1).checked_add(2), Some(PodU64::from(3)));
+        assert_eq!(PodU64::MAX.checked_add(1), None);
+        assert_eq!(PodU64::ZERO.checked_sub(1), None);
+        assert_eq!(PodU64::MAX.saturating_add(1), PodU64::MAX);
+        assert_eq!(PodU64::from(1).saturating_sub(2), 0);
+
+        assert_eq!(PodI64::ZERO.checked_sub(1), Some(PodI64::from(-1)));
+        assert_eq!(PodI64::from(i64::MIN).checked_sub(1), None);
+        assert_eq!(PodI64::from(i64::MAX).saturating_add(1), i64::MAX);
+    }
+}
@@ -1,24 +1,28 @@
 use crate::error::StakeError;
 
-use super::{bytes_to_u64, Delegation, Epoch, StakeHistoryGetEntry};
+use super::{utils::DataLen, Delegation, Epoch, PodU64, StakeHistoryGetEntry};
 
 #[repr(C)]
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub struct Stake {
     pub delegation: Delegation,
     /// credits observed is credits from vote account state when delegated or redeemed
-    credits_observed: [u8; 8], //u64
+    credits_observed: PodU64,
+}
+
+impl DataLen for Stake {
+    const LEN: usize = core::mem::size_of::<Stake>();
 }
 
 impl Stake {
     #[inline(always)]
     pub fn set_credits_observed(&mut self, credits_observed: u64) {
-        self.credits_observed = credits_observed.to_le_bytes();
+        self.credits_observed = credits_observed.into();
     }
 
     #[inline(always)]
     pub fn credits_observed(&self) -> u64 {
-        u64::from_le_bytes(self.credits_observed)
+        self.credits_observed.get()
     }
 
     pub fn stake<T: StakeHistoryGetEntry>(
@@ -36,24 +40,17 @@ impl Stake {
         remaining_stake_delta: u64,
         split_stake_amount: u64,
     ) -> Result<Self, StakeError> {
-        if remaining_stake_delta > bytes_to_u64(self.delegation.stake) {
+        if remaining_stake_delta > self.delegation.stake.get() {
             return Err(StakeError::InsufficientStake);
         }
-        self.delegation.stake = bytes_to_u64(self.delegation.stake)
-            .saturating_sub(remaining_stake_delta)
-            .to_le_bytes();
-        let new = Self {
-            delegation: Delegation {
-                stake: split_stake_amount.to_le_bytes(),
-                ..self.delegation
-            },
-            ..*self
-        };
+        self.delegation.stake = self.delegation.stake.saturating_sub(remaining_stake_delta);
+        let mut new = *self;
+        new.delegation.stake = split_stake_amount.into();
         Ok(new)
     }
 
     pub fn deactivate(&mut self, epoch: Epoch) -> Result<(), StakeError> {
-        if bytes_to_u64(self.delegation.deactivation_epoch) != u64::MAX {
+        if self.delegation.deactivation_epoch != u64::MAX {
             Err(StakeError::AlreadyDeactivated)
         } else {
             self.delegation.deactivation_epoch = epoch;
@@ -1,3 +1,5 @@
+use pinocchio::program_error::ProgramError;
+
 /*
 #[cfg_attr(feature = "frozen-abi", derive(solana_frozen_abi_macro::AbiExample))]
 #[cfg_attr(
@@ -11,3 +13,22 @@ pub enum StakeAuthorize {
     Staker,
     Withdrawer,
 }
+
+impl StakeAuthorize {
+    /// Length of the bincode encoding, a u32 LE discriminant
+    pub const LEN: usize = 4;
+
+    /// Decodes the bincode encoding at the start of `data`
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        let discriminant = data
+            .get(..Self::LEN)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        match discriminant {
+            0 => Ok(Self::Staker),
+            1 => Ok(Self::Withdrawer),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
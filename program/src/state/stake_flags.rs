@@ -1,10 +1,21 @@
+use super::utils::DataLen;
+
 #[repr(C)]
 #[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord, Hash, Debug)]
 pub struct StakeFlags {
     bits: u8,
 }
 
+impl DataLen for StakeFlags {
+    const LEN: usize = core::mem::size_of::<StakeFlags>();
+}
+
 impl StakeFlags {
+    /// Set on stake moved by the deprecated `Redelegate`: the stake may only be
+    /// deactivated once it is fully active
+    pub const MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED: Self =
+        Self { bits: 0b0000_0001 };
+
     pub const fn empty() -> Self {
         Self { bits: 0 }
     }
@@ -1,6 +1,8 @@
 use crate::declare_sysvar_id;
 use pinocchio::pubkey::Pubkey;
 use pinocchio::sysvars::clock::Epoch;
+
+use super::PodU64;
 extern crate alloc;
 
 //use {solana_sysvar_id::declare_sysvar_id, std::ops::Deref};
@@ -16,38 +18,36 @@ pub trait SysvarId {
     fn check_id(pubkey: &Pubkey) -> bool;
 }
 
-/// Declares an ID that implements [`SysvarId`].
-
 pub const MAX_ENTRIES: usize = 512; // it should never take as many as 512 epochs to warm up or cool down
 
 #[repr(C)]
 #[derive(Debug, PartialEq, Eq, Default, Clone)]
 pub struct StakeHistoryEntry {
-    pub effective: [u8; 8],    // effective stake at this epoch
-    pub activating: [u8; 8],   // sum of portion of stakes not fully warmed up
-    pub deactivating: [u8; 8], // requested to be cooled down, not fully deactivated yet
+    pub effective: PodU64,    // effective stake at this epoch
+    pub activating: PodU64,   // sum of portion of stakes not fully warmed up
+    pub deactivating: PodU64, // requested to be cooled down, not fully deactivated yet
 }
 
 impl StakeHistoryEntry {
-    pub fn with_effective(effective: [u8; 8]) -> Self {
+    pub fn with_effective(effective: u64) -> Self {
         Self {
-            effective,
+            effective: effective.into(),
             ..Self::default()
         }
     }
 
-    pub fn with_effective_and_activating(effective: [u8; 8], activating: [u8; 8]) -> Self {
+    pub fn with_effective_and_activating(effective: u64, activating: u64) -> Self {
         Self {
-            effective,
-            activating,
+            effective: effective.into(),
+            activating: activating.into(),
             ..Self::default()
         }
     }
 
     pub fn with_deactivating(deactivating: u64) -> Self {
         Self {
-            effective: deactivating.to_le_bytes(),
-            deactivating: deactivating.to_le_bytes(),
+            effective: deactivating.into(),
+            deactivating: deactivating.into(),
             ..Self::default()
         }
     }
@@ -56,19 +56,10 @@ impl StakeHistoryEntry {
 impl core::ops::Add for StakeHistoryEntry {
     type Output = StakeHistoryEntry;
     fn add(self, rhs: StakeHistoryEntry) -> Self::Output {
-        let effective = u64::from_le_bytes(self.effective);
-        let activating = u64::from_le_bytes(self.activating);
-        let deactivating = u64::from_le_bytes(self.deactivating);
         Self {
-            effective: effective
-                .saturating_add(u64::from_le_bytes(rhs.effective))
-                .to_be_bytes(),
-            activating: activating
-                .saturating_add(u64::from_le_bytes(rhs.activating))
-                .to_be_bytes(),
-            deactivating: deactivating
-                .saturating_add(u64::from_le_bytes(rhs.deactivating))
-                .to_be_bytes(),
+            effective: self.effective.saturating_add(rhs.effective.get()),
+            activating: self.activating.saturating_add(rhs.activating.get()),
+            deactivating: self.deactivating.saturating_add(rhs.deactivating.get()),
         }
     }
 }
@@ -113,3 +104,31 @@ impl StakeHistoryGetEntry for StakeHistory {
             .map(|index| self[index].1.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add() {
+        let lhs = StakeHistoryEntry {
+            effective: 256.into(),
+            activating: 1.into(),
+            deactivating: u64::MAX.into(),
+        };
+        let rhs = StakeHistoryEntry {
+            effective: 1.into(),
+            activating: 255.into(),
+            deactivating: 1.into(),
+        };
+
+        assert_eq!(
+            lhs + rhs,
+            StakeHistoryEntry {
+                effective: 257.into(),
+                activating: 256.into(),
+                deactivating: u64::MAX.into(),
+            }
+        );
+    }
+}
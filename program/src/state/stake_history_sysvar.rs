@@ -12,33 +12,19 @@
 //! [`SysvarId::id`]: https://docs.rs/solana-sysvar-id/latest/solana_sysvar_id/trait.SysvarId.html
 //! [`SysvarId::check_id`]: https://docs.rs/solana-sysvar-id/latest/solana_sysvar_id/trait.SysvarId.html#tymethod.check_id
 
-use pinocchio::{
-    pubkey::{self, Pubkey},
-    sysvars::clock::Epoch,
-};
+use pinocchio::sysvars::clock::Epoch;
 
 pub mod stake_history_id {
-    pinocchio_pubkey::declare_id!("SysvarS1otHistory11111111111111111111111111");
+    pinocchio_pubkey::declare_id!("SysvarStakeHistory1111111111111111111111111");
 }
 
-pub use stake_history_id::{check_id, id, ID};
-pub const MAX_ENTRIES: usize = 512; // it should never take as many as 512 epochs to warm up or cool down
+// `stake_history` declares the same sysvar id, re-exporting its items keeps the
+// globs in `state` unambiguous
+pub use super::stake_history::{check_id, id, ID, MAX_ENTRIES};
 
 use crate::state::get_sysvar;
 
-use super::{StakeHistoryEntry, StakeHistoryGetEntry};
-#[deprecated(
-    since = "2.2.0",
-    note = "Use solana_stake_interface::stake_history instead"
-)]
-#[cfg(feature = "bincode")]
-impl Sysvar for StakeHistory {
-    // override
-    fn size_of() -> usize {
-        // hard-coded so that we don't have to construct an empty
-        16392 // golden, update if MAX_ENTRIES changes
-    }
-}
+use super::{PodU64, StakeHistoryEntry, StakeHistoryGetEntry};
 
 // we do not provide Default because this requires the real current epoch
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -80,13 +66,13 @@ impl StakeHistoryGetEntry for StakeHistorySysvar {
         match result {
             Ok(()) => {
                 // All safe because `entry_buf` is a 32-length array
-                let entry_epoch: [u8; 8] = entry_buf[0..8].try_into().unwrap();
-                let effective = entry_buf[8..16].try_into().unwrap();
-                let activating = entry_buf[16..24].try_into().unwrap();
-                let deactivating = entry_buf[24..32].try_into().unwrap();
+                let [entry_epoch, effective, activating, deactivating] =
+                    [0, 8, 16, 24].map(|offset| {
+                        PodU64::from_le_bytes(entry_buf[offset..offset + 8].try_into().unwrap())
+                    });
 
                 // this would only fail if stake history skipped an epoch or the binary format of the sysvar changed
-                assert_eq!(u64::from_le_bytes(entry_epoch), target_epoch);
+                assert_eq!(entry_epoch, target_epoch);
 
                 Some(StakeHistoryEntry {
                     effective,
@@ -99,14 +85,32 @@ impl StakeHistoryGetEntry for StakeHistorySysvar {
     }
 }
 
-/*
-
-//---------------------------- Fix Tests Later ----------------------------------------
 #[cfg(test)]
 mod tests {
-    use crate::state::StakeHistory;
+    use solana_sdk::stake_history::{
+        StakeHistory as NativeStakeHistory, StakeHistoryEntry as NativeStakeHistoryEntry,
+    };
 
     use super::*;
+    use crate::state::{with_syscall_stubs, StakeHistory, SysvarStubs};
+
+    fn native_entry(entry: &StakeHistoryEntry) -> NativeStakeHistoryEntry {
+        NativeStakeHistoryEntry {
+            effective: entry.effective.get(),
+            activating: entry.activating.get(),
+            deactivating: entry.deactivating.get(),
+        }
+    }
+
+    /// Serves `stake_history` as the stake history sysvar while `f` runs
+    fn with_stake_history<R>(stake_history: &StakeHistory, f: impl FnOnce() -> R) -> R {
+        let mut native = NativeStakeHistory::default();
+        for (epoch, entry) in stake_history.iter() {
+            native.add(*epoch, native_entry(entry));
+        }
+
+        with_syscall_stubs(SysvarStubs::default().with_sysvar(&ID, &native), f)
+    }
 
     #[test]
     fn test_stake_history() {
@@ -116,7 +120,7 @@ mod tests {
             stake_history.add(
                 i,
                 StakeHistoryEntry {
-                    activating: i,
+                    activating: i.into(),
                     ..StakeHistoryEntry::default()
                 },
             );
@@ -127,7 +131,7 @@ mod tests {
         assert_eq!(
             stake_history.get(1),
             Some(&StakeHistoryEntry {
-                activating: 1,
+                activating: 1.into(),
                 ..StakeHistoryEntry::default()
             })
         );
@@ -135,29 +139,26 @@ mod tests {
 
     #[test]
     fn test_id() {
-        assert_eq!(StakeHistory::id(), crate::helpers::stake_history::id());
+        assert_eq!(ID, solana_sdk::sysvar::stake_history::ID.to_bytes());
     }
 
     #[test]
     fn test_size_of() {
-        let mut stake_history = StakeHistory::default();
+        let mut stake_history = NativeStakeHistory::default();
         for i in 0..MAX_ENTRIES as u64 {
             stake_history.add(
                 i,
-                StakeHistoryEntry {
+                NativeStakeHistoryEntry {
                     activating: i,
-                    ..StakeHistoryEntry::default()
+                    ..NativeStakeHistoryEntry::default()
                 },
             );
         }
 
-        assert_eq!(
-            bincode::serialized_size(&stake_history).unwrap() as usize,
+        // golden size of the sysvar, it has MAX_ENTRIES entries from epoch 512
+        assert_eq!(bincode::serialized_size(&stake_history).unwrap(), 16392);
 
-            StakeHistory::size_of()
-        );
-
-        let stake_history_inner: Vec<(Epoch, StakeHistoryEntry)> =
+        let stake_history_inner: Vec<(Epoch, NativeStakeHistoryEntry)> =
             bincode::deserialize(&bincode::serialize(&stake_history).unwrap()).unwrap();
         let epoch_entry = stake_history_inner.into_iter().next().unwrap();
 
@@ -167,14 +168,12 @@ mod tests {
         );
     }
 
-    // TODO
-    //#[serial]
     #[test]
     fn test_stake_history_get_entry() {
         let unique_entry_for_epoch = |epoch: u64| StakeHistoryEntry {
-            activating: epoch.saturating_mul(2),
-            deactivating: epoch.saturating_mul(3),
-            effective: epoch.saturating_mul(5),
+            activating: epoch.saturating_mul(2).into(),
+            deactivating: epoch.saturating_mul(3).into(),
+            effective: epoch.saturating_mul(5).into(),
         };
 
         let current_epoch = MAX_ENTRIES.saturating_add(2) as u64;
@@ -187,42 +186,36 @@ mod tests {
         assert_eq!(stake_history.len(), MAX_ENTRIES);
         assert_eq!(stake_history.iter().map(|entry| entry.0).min().unwrap(), 2);
 
-        // set up sol_get_sysvar
-
-        // TODO
-
-        //mock_get_sysvar_syscall(&bincode::serialize(&stake_history).unwrap());
-
-        // make a syscall interface object
-        let stake_history_sysvar = StakeHistorySysvar(current_epoch);
+        with_stake_history(&stake_history, || {
+            // make a syscall interface object
+            let stake_history_sysvar = StakeHistorySysvar(current_epoch);
 
-        // now test the stake history interfaces
+            // now test the stake history interfaces
 
-        assert_eq!(stake_history.get(0), None);
-        assert_eq!(stake_history.get(1), None);
-        assert_eq!(stake_history.get(current_epoch), None);
+            assert_eq!(stake_history.get(0), None);
+            assert_eq!(stake_history.get(1), None);
+            assert_eq!(stake_history.get(current_epoch), None);
 
-        assert_eq!(stake_history.get_entry(0), None);
-        assert_eq!(stake_history.get_entry(1), None);
-        assert_eq!(stake_history.get_entry(current_epoch), None);
+            assert_eq!(stake_history.get_entry(0), None);
+            assert_eq!(stake_history.get_entry(1), None);
+            assert_eq!(stake_history.get_entry(current_epoch), None);
 
-        assert_eq!(stake_history_sysvar.get_entry(0), None);
-        assert_eq!(stake_history_sysvar.get_entry(1), None);
-        assert_eq!(stake_history_sysvar.get_entry(current_epoch), None);
+            assert_eq!(stake_history_sysvar.get_entry(0), None);
+            assert_eq!(stake_history_sysvar.get_entry(1), None);
+            assert_eq!(stake_history_sysvar.get_entry(current_epoch), None);
 
-        for i in 2..current_epoch {
-            let entry = Some(unique_entry_for_epoch(i));
+            for i in 2..current_epoch {
+                let entry = Some(unique_entry_for_epoch(i));
 
-            assert_eq!(stake_history.get(i), entry.as_ref(),);
+                assert_eq!(stake_history.get(i), entry.as_ref());
 
-            assert_eq!(stake_history.get_entry(i), entry,);
+                assert_eq!(stake_history.get_entry(i), entry);
 
-            assert_eq!(stake_history_sysvar.get_entry(i), entry,);
-        }
+                assert_eq!(stake_history_sysvar.get_entry(i), entry);
+            }
+        });
     }
 
-    // TODO
-    //#[serial]
     #[test]
     fn test_stake_history_get_entry_zero() {
         let mut current_epoch = 0;
@@ -231,18 +224,16 @@ mod tests {
         let stake_history = StakeHistory::default();
         assert_eq!(stake_history.len(), 0);
 
-        //mock_get_sysvar_syscall(&bincode::serialize(&stake_history).unwrap());
-        let stake_history_sysvar = StakeHistorySysvar(current_epoch);
+        with_stake_history(&stake_history, || {
+            let stake_history_sysvar = StakeHistorySysvar(current_epoch);
 
-        assert_eq!(stake_history.get(0), None);
-        assert_eq!(stake_history.get_entry(0), None);
-        assert_eq!(stake_history_sysvar.get_entry(0), None);
+            assert_eq!(stake_history.get(0), None);
+            assert_eq!(stake_history.get_entry(0), None);
+            assert_eq!(stake_history_sysvar.get_entry(0), None);
+        });
 
         // next test that we can get a zeroth entry in the first epoch
-        let entry_zero = StakeHistoryEntry {
-            effective: 100,
-            ..StakeHistoryEntry::default()
-        };
+        let entry_zero = StakeHistoryEntry::with_effective(100);
         let entry = Some(entry_zero.clone());
 
         let mut stake_history = StakeHistory::default();
@@ -250,26 +241,42 @@ mod tests {
         assert_eq!(stake_history.len(), 1);
         current_epoch = current_epoch.saturating_add(1);
 
-        // TODO
-        // mock_get_sysvar_syscall(&bincode::serialize(&stake_history).unwrap());
-        let stake_history_sysvar = StakeHistorySysvar(current_epoch);
+        with_stake_history(&stake_history, || {
+            let stake_history_sysvar = StakeHistorySysvar(current_epoch);
 
-        assert_eq!(stake_history.get(0), entry.as_ref());
-        assert_eq!(stake_history.get_entry(0), entry);
-        assert_eq!(stake_history_sysvar.get_entry(0), entry);
+            assert_eq!(stake_history.get(0), entry.as_ref());
+            assert_eq!(stake_history.get_entry(0), entry);
+            assert_eq!(stake_history_sysvar.get_entry(0), entry);
+        });
 
         // finally test that we can still get a zeroth entry in later epochs
         stake_history.add(current_epoch, StakeHistoryEntry::default());
         assert_eq!(stake_history.len(), 2);
         current_epoch = current_epoch.saturating_add(1);
 
-        // TODO
-        // mock_get_sysvar_syscall(&bincode::serialize(&stake_history).unwrap());
-        let stake_history_sysvar = StakeHistorySysvar(current_epoch);
+        with_stake_history(&stake_history, || {
+            let stake_history_sysvar = StakeHistorySysvar(current_epoch);
+
+            assert_eq!(stake_history.get(0), entry.as_ref());
+            assert_eq!(stake_history.get_entry(0), entry);
+            assert_eq!(stake_history_sysvar.get_entry(0), entry);
+        });
+    }
+
+    #[test]
+    fn test_stake_history_get_entry_unavailable() {
+        // the default stubs have no sysvars, like a runtime without the syscall
+        with_syscall_stubs(SysvarStubs::default(), || {
+            assert_eq!(StakeHistorySysvar(10).get_entry(5), None);
+        });
+
+        // a history shorter than the current epoch implies is read past its end
+        let mut stake_history = StakeHistory::default();
+        stake_history.add(0, StakeHistoryEntry::with_effective(100));
 
-        assert_eq!(stake_history.get(0), entry.as_ref());
-        assert_eq!(stake_history.get_entry(0), entry);
-        assert_eq!(stake_history_sysvar.get_entry(0), entry);
+        with_stake_history(&stake_history, || {
+            assert_eq!(StakeHistorySysvar(1).get_entry(0).unwrap().effective, 100);
+            assert_eq!(StakeHistorySysvar(3).get_entry(0), None);
+        });
     }
 }
- */
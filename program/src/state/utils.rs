@@ -1,26 +1,21 @@
 use pinocchio::{
     account_info::{AccountInfo, Ref},
     program_error::ProgramError,
-    pubkey::Pubkey,
-    sysvars::{
-        clock::{Clock, Epoch},
-        rent::Rent,
-        Sysvar,
-    },
+    pubkey::{Pubkey, MAX_SEED_LEN},
+    sysvars::{clock::Clock, rent::Rent, Sysvar},
     ProgramResult, SUCCESS,
 };
 
 extern crate alloc;
 use super::{
-    get_stake_state, set_stake_state, Meta, StakeAuthorize, StakeStateV2,
-    DEFAULT_WARMUP_COOLDOWN_RATE,
+    get_stake_state, set_stake_state, Authorized, Lockup, MergeKind, Meta, Stake, StakeAuthorize,
+    StakeFlags, StakeHistorySysvar, StakeStateV2,
 };
 use crate::consts::{
-    FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL, LAMPORTS_PER_SOL, MAX_SIGNERS,
-    NEW_WARMUP_COOLDOWN_RATE, SYSVAR,
+    CLOCK_ID, FEATURE_STAKE_RAISE_MINIMUM_DELEGATION_TO_1_SOL, LAMPORTS_PER_SOL, MAX_SIGNERS,
+    PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH, SYSVAR,
 };
-use alloc::boxed::Box;
-use core::cell::UnsafeCell;
+use crate::error::StakeError;
 
 pub trait DataLen {
     const LEN: usize;
@@ -30,6 +25,9 @@ pub trait Initialized {
     fn is_initialized(&self) -> bool;
 }
 
+/// # Safety
+///
+/// `T` must have an alignment of 1 and be valid for any bytes of its length.
 #[inline(always)]
 pub unsafe fn load_acc<T: DataLen + Initialized>(bytes: &[u8]) -> Result<&T, ProgramError> {
     load_acc_unchecked::<T>(bytes).and_then(|acc| {
@@ -41,6 +39,9 @@ pub unsafe fn load_acc<T: DataLen + Initialized>(bytes: &[u8]) -> Result<&T, Pro
     })
 }
 
+/// # Safety
+///
+/// `T` must have an alignment of 1 and be valid for any bytes of its length.
 #[inline(always)]
 pub unsafe fn load_acc_unchecked<T: DataLen>(bytes: &[u8]) -> Result<&T, ProgramError> {
     if bytes.len() != T::LEN {
@@ -49,6 +50,9 @@ pub unsafe fn load_acc_unchecked<T: DataLen>(bytes: &[u8]) -> Result<&T, Program
     Ok(&*(bytes.as_ptr() as *const T))
 }
 
+/// # Safety
+///
+/// `T` must have an alignment of 1 and be valid for any bytes of its length.
 #[inline(always)]
 pub unsafe fn load_acc_mut<T: DataLen + Initialized>(
     bytes: &mut [u8],
@@ -62,6 +66,9 @@ pub unsafe fn load_acc_mut<T: DataLen + Initialized>(
     })
 }
 
+/// # Safety
+///
+/// `T` must have an alignment of 1 and be valid for any bytes of its length.
 #[inline(always)]
 pub unsafe fn load_acc_mut_unchecked<T: DataLen>(bytes: &mut [u8]) -> Result<&mut T, ProgramError> {
     if bytes.len() != T::LEN {
@@ -70,18 +77,27 @@ pub unsafe fn load_acc_mut_unchecked<T: DataLen>(bytes: &mut [u8]) -> Result<&mu
     Ok(&mut *(bytes.as_mut_ptr() as *mut T))
 }
 
+/// # Safety
+///
+/// `T` must have an alignment of 1 and be valid for any bytes of its length.
 #[inline(always)]
 pub unsafe fn load_ix_data<T: DataLen>(bytes: &[u8]) -> Result<&T, ProgramError> {
     if bytes.len() != T::LEN {
-        return Err(ProgramError::InvalidInstructionData.into());
+        return Err(ProgramError::InvalidInstructionData);
     }
     Ok(&*(bytes.as_ptr() as *const T))
 }
 
+/// # Safety
+///
+/// `T` must have no padding, every byte of it is exposed.
 pub unsafe fn to_bytes<T: DataLen>(data: &T) -> &[u8] {
     core::slice::from_raw_parts(data as *const T as *const u8, T::LEN)
 }
 
+/// # Safety
+///
+/// `T` must have no padding, every byte of it is exposed.
 pub unsafe fn to_mut_bytes<T: DataLen>(data: &mut T) -> &mut [u8] {
     core::slice::from_raw_parts_mut(data as *mut T as *mut u8, T::LEN)
 }
@@ -107,6 +123,53 @@ pub fn collect_signers(
     Ok(signer_len)
 }
 
+const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+
+/// SHA-256 of the concatenation of `vals`, through the `sol_sha256` syscall on
+/// chain
+pub fn hashv(vals: &[&[u8]]) -> [u8; 32] {
+    let mut hash = [0; 32];
+
+    #[cfg(target_os = "solana")]
+    unsafe {
+        pinocchio::syscalls::sol_sha256(
+            vals as *const _ as *const u8,
+            vals.len() as u64,
+            hash.as_mut_ptr(),
+        );
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        for val in vals {
+            hasher.update(val);
+        }
+        hash.copy_from_slice(&hasher.finalize());
+    }
+
+    hash
+}
+
+/// No-alloc equivalent of `Pubkey::create_with_seed`
+pub fn create_with_seed(
+    base: &Pubkey,
+    seed: &[u8],
+    owner: &Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    if seed.len() > MAX_SEED_LEN {
+        return Err(ProgramError::MaxSeedLengthExceeded);
+    }
+
+    if owner.ends_with(PDA_MARKER) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    Ok(hashv(&[base, seed, owner]))
+}
+
 pub fn next_account_info<'a, I: Iterator<Item = &'a AccountInfo>>(
     iter: &mut I,
 ) -> Result<&'a AccountInfo, ProgramError> {
@@ -171,7 +234,7 @@ pub(crate) fn validate_split_amount(
     // splitting: EITHER at least the minimum balance, OR zero (in this case the
     // source account is transferring all lamports to new destination account,
     // and the source account will be closed)
-    let source_minimum_balance = u64::from_le_bytes(source_meta.rent_exempt_reserve)
+    let source_minimum_balance = source_meta.rent_exempt_reserve.get()
         .saturating_add(additional_required_lamports);
     let source_remaining_balance = source_lamports.saturating_sub(split_lamports);
     if source_remaining_balance == 0 {
@@ -219,38 +282,33 @@ pub(crate) fn validate_split_amount(
     })
 }
 
-//-------------- Solana Program Sysvar Copies ---------------
-
-//---------------- This Get Sysvar was assisted by AI, needs to be checked ----------------------
-//For this syscall mock, unlike solana program we use single thread to mantain the no_std enviorement
-//Defining a generic Lazy<T> struct with interior mutability
-pub struct Lazy<T> {
-    value: UnsafeCell<Option<T>>,
+/// After calling `validate_delegated_amount()`, this struct contains calculated
+/// values that are used by the caller.
+#[derive(Copy, Clone, Debug, Default)]
+pub(crate) struct ValidatedDelegatedInfo {
+    pub stake_amount: u64,
 }
 
-impl<T> Lazy<T> {
-    pub const fn new() -> Self {
-        Self {
-            value: UnsafeCell::new(None),
-        }
-    }
-
-    pub fn get_or_init<F: FnOnce() -> T>(&self, init: F) -> &T {
-        // SAFETY: Only safe because Solana programs are single-threaded.
-        // So its ok to get mutable access (even though `self` is shared!)
-        unsafe {
-            let value = &mut *self.value.get();
-            if value.is_none() {
-                *value = Some(init());
-            }
-            value.as_ref().unwrap()
-        }
+/// Ensure the stake delegation amount is valid.  This checks that the account
+/// meets the minimum balance requirements of delegated stake.  If not, return
+/// an error.
+pub(crate) fn validate_delegated_amount(
+    account: &AccountInfo,
+    meta: &Meta,
+) -> Result<ValidatedDelegatedInfo, ProgramError> {
+    let stake_amount = account
+        .lamports()
+        .saturating_sub(meta.rent_exempt_reserve.get()); // can't stake the rent
+
+    // Stake accounts may be initialized with a stake amount below the minimum
+    // delegation so check that the minimum is met before delegation.
+    if stake_amount < get_minimum_delegation() {
+        return Err(StakeError::InsufficientDelegation.into());
     }
+    Ok(ValidatedDelegatedInfo { stake_amount })
 }
 
-static SYSCALL_STUBS: Lazy<Box<dyn SyscallStubs>> = Lazy::new();
-
-unsafe impl<T> Sync for Lazy<T> {} //although this is telling that is available for multithreading, we know it wont happen
+//-------------- Solana Program Sysvar Copies ---------------
 
 /// Builtin return values occupy the upper 32 bits
 const BUILTIN_BIT_SHIFT: usize = 32;
@@ -262,35 +320,158 @@ macro_rules! to_builtin {
 
 pub const UNSUPPORTED_SYSVAR: u64 = to_builtin!(17);
 
-pub trait SyscallStubs: Sync + Send {
-    fn sol_get_sysvar(
-        &self,
-        _sysvar_id_addr: *const u8,
-        _var_addr: *mut u8,
-        _offset: u64,
-        _length: u64,
+/// Returned by `sol_get_sysvar` when the requested range is out of bounds
+pub const OFFSET_LENGTH_EXCEEDS_SYSVAR: u64 = 1;
+/// Returned by `sol_get_sysvar` when the sysvar doesn't exist
+pub const SYSVAR_NOT_FOUND: u64 = 2;
+
+/// Off-chain stand-ins for the syscalls, which the on-chain build calls
+/// directly. They need `std`, so only tests and `std` builds can install them.
+#[cfg(all(not(target_os = "solana"), any(test, feature = "std")))]
+mod syscall_stubs {
+    use super::*;
+    use alloc::{boxed::Box, vec::Vec};
+    use std::sync::{Mutex, PoisonError};
+
+    /// Off-chain implementations of the syscalls the program makes, see
+    /// `set_syscall_stubs`
+    pub trait SyscallStubs: Sync + Send {
+        fn sol_get_sysvar(
+            &self,
+            _sysvar_id_addr: *const u8,
+            _var_addr: *mut u8,
+            _offset: u64,
+            _length: u64,
+        ) -> u64 {
+            UNSUPPORTED_SYSVAR
+        }
+    }
+
+    pub struct DefaultSyscallStubs {}
+
+    impl SyscallStubs for DefaultSyscallStubs {}
+
+    /// Serves sysvars from memory, so code reading them through `get_sysvar` can
+    /// run off-chain:
+    ///
+    /// ```ignore
+    /// set_syscall_stubs(Box::new(
+    ///     SysvarStubs::default().with_sysvar(&stake_history_id::ID, &native_stake_history),
+    /// ));
+    /// ```
+    #[derive(Default)]
+    pub struct SysvarStubs {
+        sysvars: Vec<(Pubkey, Vec<u8>)>,
+    }
+
+    impl SysvarStubs {
+        /// Serves `sysvar` under `id`. It's serialized with bincode like the
+        /// runtime does, so the native `StakeHistory`, `EpochRewards`, `Clock` and
+        /// `Rent` types produce the bytes a validator would return.
+        pub fn with_sysvar<T: serde::Serialize>(self, id: &Pubkey, sysvar: &T) -> Self {
+            self.with_sysvar_data(id, bincode::serialize(sysvar).unwrap())
+        }
+
+        /// Serves `data` as is under `id`
+        pub fn with_sysvar_data(mut self, id: &Pubkey, data: Vec<u8>) -> Self {
+            self.sysvars.retain(|(key, _)| key != id);
+            self.sysvars.push((*id, data));
+            self
+        }
+    }
+
+    impl SyscallStubs for SysvarStubs {
+        // the pointers come from `get_sysvar`, like the syscall's arguments
+        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+        fn sol_get_sysvar(
+            &self,
+            sysvar_id_addr: *const u8,
+            var_addr: *mut u8,
+            offset: u64,
+            length: u64,
+        ) -> u64 {
+            // SAFETY: `get_sysvar` passes a pubkey and a buffer of at least `length` bytes
+            let sysvar_id = unsafe { &*(sysvar_id_addr as *const Pubkey) };
+
+            let Some((_, data)) = self.sysvars.iter().find(|(key, _)| key == sysvar_id) else {
+                return SYSVAR_NOT_FOUND;
+            };
+            let Some(bytes) = offset
+                .checked_add(length)
+                .and_then(|end| data.get(offset as usize..end as usize))
+            else {
+                return OFFSET_LENGTH_EXCEEDS_SYSVAR;
+            };
+
+            unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), var_addr, bytes.len()) };
+            SUCCESS
+        }
+    }
+
+    /// The installed stubs, behind a lock since tests run on several threads
+    static SYSCALL_STUBS: Mutex<Option<Box<dyn SyscallStubs>>> = Mutex::new(None);
+
+    /// Runs `f` on the installed stubs. A stub that panicked leaves the lock
+    /// poisoned, which is ignored so the next test can still run.
+    fn with_stubs<R>(f: impl FnOnce(&mut Box<dyn SyscallStubs>) -> R) -> R {
+        let mut stubs = SYSCALL_STUBS.lock().unwrap_or_else(PoisonError::into_inner);
+        f(stubs.get_or_insert_with(|| Box::new(DefaultSyscallStubs {})))
+    }
+
+    /// Replaces the syscalls used off-chain, returning the previous stubs. They
+    /// are global, so tests installing their own must not run concurrently with
+    /// other tests reading sysvars.
+    pub fn set_syscall_stubs(stubs: Box<dyn SyscallStubs>) -> Box<dyn SyscallStubs> {
+        with_stubs(|installed| core::mem::replace(installed, stubs))
+    }
+
+    pub(crate) fn sol_get_sysvar(
+        sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
     ) -> u64 {
-        UNSUPPORTED_SYSVAR
+        with_stubs(|stubs| stubs.sol_get_sysvar(sysvar_id_addr, var_addr, offset, length))
     }
 }
 
-pub struct DefaultSyscallStubs {}
-
-impl SyscallStubs for DefaultSyscallStubs {}
+#[cfg(all(not(target_os = "solana"), any(test, feature = "std")))]
+pub use syscall_stubs::*;
 
-#[allow(dead_code)]
-pub(crate) fn sol_get_sysvar(
-    sysvar_id_addr: *const u8,
-    var_addr: *mut u8,
-    offset: u64,
-    length: u64,
+/// Without stubs, off-chain builds have no sysvars
+#[cfg(all(not(target_os = "solana"), not(any(test, feature = "std"))))]
+fn sol_get_sysvar(
+    _sysvar_id_addr: *const u8,
+    _var_addr: *mut u8,
+    _offset: u64,
+    _length: u64,
 ) -> u64 {
-    SYSCALL_STUBS
-        .get_or_init(|| Box::new(DefaultSyscallStubs {}))
-        .sol_get_sysvar(sysvar_id_addr, var_addr, offset, length)
+    UNSUPPORTED_SYSVAR
 }
 
-//---------------- End of AI assistance ----------------------
+/// Runs `f` with `stubs` installed, one test at a time
+#[cfg(test)]
+pub(crate) fn with_syscall_stubs<R>(
+    stubs: impl SyscallStubs + 'static,
+    f: impl FnOnce() -> R,
+) -> R {
+    // puts the previous stubs back even if `f` panics
+    struct Restore(Option<Box<dyn SyscallStubs>>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            set_syscall_stubs(self.0.take().unwrap());
+        }
+    }
+
+    static SERIAL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _serial = SERIAL
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let _restore = Restore(Some(set_syscall_stubs(Box::new(stubs))));
+    f()
+}
 
 /// Handler for retrieving a slice of sysvar data from the `sol_get_sysvar`
 /// syscall.
@@ -320,12 +501,21 @@ pub fn get_sysvar(
 
     match result {
         SUCCESS => Ok(()),
+        OFFSET_LENGTH_EXCEEDS_SYSVAR => Err(ProgramError::InvalidArgument),
+        SYSVAR_NOT_FOUND => Err(ProgramError::UnsupportedSysvar),
         e => Err(e.into()),
     }
 }
 
+/// Overflowing lamport arithmetic means the account could never cover the
+/// amount, which native reports as `InsufficientFunds`
+#[inline(always)]
+pub fn checked_add(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_add(b).ok_or(ProgramError::InsufficientFunds)
+}
+
 pub fn to_program_error(e: ProgramError) -> ProgramError {
-    ProgramError::try_from(e).unwrap_or(ProgramError::InvalidAccountData)
+    e
 }
 
 #[inline(always)]
@@ -338,6 +528,134 @@ pub fn get_minimum_delegation() -> u64 {
     }
 }
 
+pub fn do_initialize(
+    stake_account_info: &AccountInfo,
+    authorized: Authorized,
+    lockup: Lockup,
+    rent: &Rent,
+) -> ProgramResult {
+    if stake_account_info.data_len() != StakeStateV2::size_of() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if matches!(
+        get_stake_state(stake_account_info)?,
+        StakeStateV2::Uninitialized
+    ) {
+        let rent_exempt_reserve = rent.minimum_balance(stake_account_info.data_len());
+        if stake_account_info.lamports() >= rent_exempt_reserve {
+            let stake_state = StakeStateV2::Initialized(Meta {
+                rent_exempt_reserve: rent_exempt_reserve.into(),
+                authorized,
+                lockup,
+            });
+
+            set_stake_state(stake_account_info, &stake_state)
+        } else {
+            Err(ProgramError::InsufficientFunds)
+        }
+    } else {
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+pub(crate) fn deactivate_stake(
+    stake: &mut Stake,
+    stake_flags: &mut StakeFlags,
+    epoch: u64,
+) -> ProgramResult {
+    if stake_flags.contains(StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED) {
+        let stake_history = StakeHistorySysvar(epoch);
+        // when MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED flag is set on stake_flags,
+        // deactivation is only permitted when the stake delegation activating amount is zero.
+        let status = stake.delegation.stake_activating_and_deactivating(
+            epoch.into(),
+            &stake_history,
+            PERPETUAL_NEW_WARMUP_COOLDOWN_RATE_EPOCH,
+        );
+        if status.activating.get() != 0 {
+            Err(StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted.into())
+        } else {
+            stake.deactivate(epoch.into())?;
+            // After deactivation, need to clear `MustFullyActivateBeforeDeactivationIsPermitted` flag if any
+            // so that future activation and deactivation are not subject to that restriction.
+            stake_flags.remove(StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED);
+            Ok(())
+        }
+    } else {
+        stake.deactivate(epoch.into())?;
+        Ok(())
+    }
+}
+
+/// Checks shared by `MoveStake` and `MoveLamports`, returning the merge kinds of
+/// the source and destination accounts
+pub(crate) fn move_stake_or_lamports_shared_checks(
+    source_stake_account_info: &AccountInfo,
+    lamports: u64,
+    destination_stake_account_info: &AccountInfo,
+    stake_authority_info: &AccountInfo,
+) -> Result<(MergeKind, MergeKind), ProgramError> {
+    // authority must sign
+    if !stake_authority_info.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let signers = core::slice::from_ref(stake_authority_info.key());
+
+    // confirm not the same account
+    if source_stake_account_info.key() == destination_stake_account_info.key() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // source and destination must be writable
+    // runtime guards against unowned writes, but MoveStake and MoveLamports are defined by SIMD
+    // we check explicitly to avoid any possibility of a successful no-op that never attempts to write
+    if !source_stake_account_info.is_writable() || !destination_stake_account_info.is_writable() {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // must move something
+    if lamports == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let clock = Clock::get()?;
+    let stake_history = StakeHistorySysvar(clock.epoch);
+
+    // get_if_mergeable ensures accounts are not partly activated or in any form of deactivating
+    // we still need to exclude activating state ourselves
+    let source_merge_kind = MergeKind::get_if_mergeable(
+        &get_stake_state(source_stake_account_info)?,
+        source_stake_account_info.lamports(),
+        &clock,
+        &stake_history,
+    )?;
+
+    // Authorized staker is allowed to move stake
+    source_merge_kind
+        .meta()
+        .authorized
+        .check(signers, StakeAuthorize::Staker)
+        .map_err(to_program_error)?;
+
+    // same transient assurance as with source
+    let destination_merge_kind = MergeKind::get_if_mergeable(
+        &get_stake_state(destination_stake_account_info)?,
+        destination_stake_account_info.lamports(),
+        &clock,
+        &stake_history,
+    )?;
+
+    // ensure all authorities match and lockups match if lockup is in force
+    MergeKind::metas_can_merge(
+        source_merge_kind.meta(),
+        destination_merge_kind.meta(),
+        &clock,
+    )?;
+
+    Ok((source_merge_kind, destination_merge_kind))
+}
+
 pub fn do_authorize(
     stake_account_info: &AccountInfo,
     signers: &[Pubkey],
@@ -346,7 +664,7 @@ pub fn do_authorize(
     custodian: Option<&Pubkey>,
     clock: &Clock,
 ) -> ProgramResult {
-    match *get_stake_state(stake_account_info)? {
+    match get_stake_state(stake_account_info)? {
         StakeStateV2::Initialized(mut meta) => {
             meta.authorized
                 .authorize(
@@ -380,7 +698,12 @@ pub fn do_authorize(
 
 //Clock doesn't have a from_account_info, so we implemt it, inspired from TokenAccount Pinocchio impl
 
-pub fn clock_from_account_info(account_info: &AccountInfo) -> Result<Ref<Clock>, ProgramError> {
+pub fn clock_from_account_info(account_info: &AccountInfo) -> Result<Ref<'_, Clock>, ProgramError> {
+    // any account can be assigned to the sysvar owner, only the key is proof
+    if account_info.key() != &CLOCK_ID {
+        return Err(ProgramError::InvalidArgument);
+    }
+
     if account_info.data_len() != core::mem::size_of::<Clock>() {
         return Err(ProgramError::InvalidAccountData);
     }
@@ -396,31 +719,129 @@ pub fn clock_from_account_info(account_info: &AccountInfo) -> Result<Ref<Clock>,
     Ok(clock_acc)
 }
 
-// Means that no more than RATE of current effective stake may be added or subtracted per
-// epoch.
+#[cfg(test)]
+mod tests {
+    use solana_sdk::sysvar;
+
+    use super::*;
+
+    #[test]
+    fn test_sysvar_stubs_clock() {
+        let clock = solana_sdk::clock::Clock {
+            slot: 1,
+            epoch_start_timestamp: -2,
+            epoch: 3,
+            leader_schedule_epoch: 4,
+            unix_timestamp: -5,
+        };
+        let stubs = SysvarStubs::default().with_sysvar(&sysvar::clock::ID.to_bytes(), &clock);
+
+        // the bincode layout is the `repr(C)` one of pinocchio's `Clock`
+        const LEN: usize = core::mem::size_of::<Clock>();
+        let mut data = [0; LEN];
+        with_syscall_stubs(stubs, || {
+            get_sysvar(&mut data, &sysvar::clock::ID.to_bytes(), 0, LEN as u64)
+        })
+        .unwrap();
+        let read = unsafe { (data.as_ptr() as *const Clock).read_unaligned() };
+
+        assert_eq!(read.slot, 1);
+        assert_eq!(read.epoch_start_timestamp, -2);
+        assert_eq!(read.epoch, 3);
+        assert_eq!(read.leader_schedule_epoch, 4);
+        assert_eq!(read.unix_timestamp, -5);
+    }
 
-pub fn warmup_cooldown_rate(
-    current_epoch: [u8; 8],
-    new_rate_activation_epoch: Option<[u8; 8]>,
-) -> f64 {
-    let current = bytes_to_u64(current_epoch);
-    let activation = new_rate_activation_epoch
-        .map(bytes_to_u64)
-        .unwrap_or(u64::MAX);
+    #[test]
+    fn test_sysvar_stubs_rent() {
+        let rent = solana_sdk::rent::Rent::default();
+        let stubs = SysvarStubs::default().with_sysvar(&sysvar::rent::ID.to_bytes(), &rent);
 
-    if current < activation {
-        DEFAULT_WARMUP_COOLDOWN_RATE
-    } else {
-        NEW_WARMUP_COOLDOWN_RATE
+        let mut data = [0; Rent::LEN];
+        with_syscall_stubs(stubs, || {
+            get_sysvar(&mut data, &sysvar::rent::ID.to_bytes(), 0, Rent::LEN as u64)
+        })
+        .unwrap();
+
+        assert_eq!(data[..8], rent.lamports_per_byte_year.to_le_bytes());
+        assert_eq!(data[8..16], rent.exemption_threshold.to_le_bytes());
+        assert_eq!(data[16], rent.burn_percent);
     }
-}
 
-pub fn add_le_bytes(lhs: [u8; 8], rhs: [u8; 8]) -> [u8; 8] {
-    u64::from_le_bytes(lhs)
-        .saturating_add(u64::from_le_bytes(rhs))
-        .to_le_bytes()
-}
+    #[test]
+    fn test_get_sysvar_errors() {
+        let id = sysvar::clock::ID.to_bytes();
+        let stubs = SysvarStubs::default().with_sysvar_data(&id, vec![1, 2, 3, 4]);
+
+        with_syscall_stubs(stubs, || {
+            let mut data = [0; 4];
+
+            assert_eq!(get_sysvar(&mut data, &id, 1, 3), Ok(()));
+            assert_eq!(data, [2, 3, 4, 0]);
+
+            assert_eq!(
+                get_sysvar(&mut data, &id, 2, 3),
+                Err(ProgramError::InvalidArgument)
+            );
+            assert_eq!(
+                get_sysvar(&mut data, &id, u64::MAX, 1),
+                Err(ProgramError::InvalidArgument)
+            );
+            // destination too small
+            assert_eq!(
+                get_sysvar(&mut data[..2], &id, 0, 3),
+                Err(ProgramError::InvalidArgument)
+            );
+            assert_eq!(
+                get_sysvar(&mut data, &sysvar::rent::ID.to_bytes(), 0, 1),
+                Err(ProgramError::UnsupportedSysvar)
+            );
+        });
+    }
+
+    #[test]
+    fn test_set_syscall_stubs() {
+        let id = sysvar::clock::ID.to_bytes();
+        let mut data = [0; 1];
+
+        with_syscall_stubs(DefaultSyscallStubs {}, || {
+            let stubs = SysvarStubs::default().with_sysvar_data(&id, vec![7]);
+            let previous = set_syscall_stubs(Box::new(stubs));
+            assert_eq!(get_sysvar(&mut data, &id, 0, 1), Ok(()));
+            assert_eq!(data, [7]);
+
+            // putting the previous stubs back removes the sysvar again
+            set_syscall_stubs(previous);
+            assert_eq!(
+                get_sysvar(&mut data, &id, 0, 1),
+                Err(ProgramError::UnsupportedSysvar)
+            );
+        });
+    }
+
+    #[test]
+    fn test_syscall_stubs_panic() {
+        struct Panicking;
 
-pub fn bytes_to_u64(bytes: [u8; 8]) -> u64 {
-    u64::from_le_bytes(bytes)
+        impl SyscallStubs for Panicking {
+            fn sol_get_sysvar(&self, _: *const u8, _: *mut u8, _: u64, _: u64) -> u64 {
+                panic!("stub failed");
+            }
+        }
+
+        let id = sysvar::clock::ID.to_bytes();
+        let mut data = [0; 1];
+
+        with_syscall_stubs(Panicking, || {
+            let result = std::panic::catch_unwind(|| get_sysvar(&mut [0; 1], &id, 0, 1));
+            assert!(result.is_err());
+
+            // the panic poisoned the lock, which the stubs ignore
+            set_syscall_stubs(Box::new(DefaultSyscallStubs {}));
+            assert_eq!(
+                get_sysvar(&mut data, &id, 0, 1),
+                Err(ProgramError::UnsupportedSysvar)
+            );
+        });
+    }
 }
@@ -0,0 +1,222 @@
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+
+use crate::consts::VOTE_PROGRAM_ID;
+
+/// `VoteStateVersions` discriminants we know how to read
+const VOTE_STATE_V1_14_11: u32 = 1;
+const VOTE_STATE_CURRENT: u32 = 2;
+
+/// node_pubkey + authorized_withdrawer + commission
+const NODE_WITHDRAWER_COMMISSION_SIZE: usize = 32 + 32 + 1;
+/// Lockout { slot: u64, confirmation_count: u32 }
+const LOCKOUT_SIZE: usize = 12;
+/// LandedVote { latency: u8, lockout: Lockout }
+const LANDED_VOTE_SIZE: usize = 1 + LOCKOUT_SIZE;
+/// (Epoch, Pubkey) entry of the authorized voters map
+const AUTHORIZED_VOTER_SIZE: usize = 8 + 32;
+/// CircBuf<(Pubkey, Epoch, Epoch)> { buf: [_; 32], idx: u64, is_empty: bool }
+const PRIOR_VOTERS_SIZE: usize = 32 * (32 + 8 + 8) + 8 + 1;
+/// (Epoch, credits, prev_credits)
+const EPOCH_CREDITS_ENTRY_SIZE: usize = 24;
+
+/// Zero-copy reader over the bincode-serialized `VoteStateVersions` held by a
+/// vote account.
+///
+/// Only the fields the stake program needs are exposed. The variable-length
+/// prefix of the account is walked once to locate `epoch_credits`.
+#[derive(Debug, Clone, Copy)]
+pub struct VoteStateView<'a> {
+    epoch_credits: &'a [u8],
+}
+
+impl<'a> VoteStateView<'a> {
+    pub fn from_bytes(data: &'a [u8]) -> Result<Self, ProgramError> {
+        let version = data
+            .get(..4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        let vote_size = match version {
+            VOTE_STATE_V1_14_11 => LOCKOUT_SIZE,
+            VOTE_STATE_CURRENT => LANDED_VOTE_SIZE,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let mut offset = 4 + NODE_WITHDRAWER_COMMISSION_SIZE;
+
+        // votes
+        let votes_len = read_len(data, &mut offset)?;
+        offset = skip(data, offset, votes_len, vote_size)?;
+
+        // root_slot
+        match data.get(offset) {
+            Some(0) => offset += 1,
+            Some(1) => offset = skip(data, offset + 1, 1, 8)?,
+            _ => return Err(ProgramError::InvalidAccountData),
+        }
+
+        // authorized_voters
+        let authorized_voters_len = read_len(data, &mut offset)?;
+        offset = skip(data, offset, authorized_voters_len, AUTHORIZED_VOTER_SIZE)?;
+
+        // prior_voters
+        offset = skip(data, offset, 1, PRIOR_VOTERS_SIZE)?;
+
+        // epoch_credits
+        let epoch_credits_len = read_len(data, &mut offset)?;
+        let end = skip(data, offset, epoch_credits_len, EPOCH_CREDITS_ENTRY_SIZE)?;
+
+        Ok(Self {
+            epoch_credits: &data[offset..end],
+        })
+    }
+
+    /// Number of entries in `epoch_credits`
+    #[inline(always)]
+    pub fn epoch_credits_len(&self) -> usize {
+        self.epoch_credits.len() / EPOCH_CREDITS_ENTRY_SIZE
+    }
+
+    /// `(epoch, credits, prev_credits)` entries of `epoch_credits`, oldest first.
+    /// Reverse it to walk the history from the latest epoch.
+    pub fn epoch_credits(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (u64, u64, u64)> + ExactSizeIterator + 'a {
+        self.epoch_credits
+            .chunks_exact(EPOCH_CREDITS_ENTRY_SIZE)
+            .map(|entry| {
+                (
+                    u64::from_le_bytes(entry[..8].try_into().unwrap()),
+                    u64::from_le_bytes(entry[8..16].try_into().unwrap()),
+                    u64::from_le_bytes(entry[16..].try_into().unwrap()),
+                )
+            })
+    }
+
+    /// Credits earned by the vote account, i.e. the `credits` of the latest
+    /// `epoch_credits` entry, or 0 if it never voted
+    pub fn credits(&self) -> u64 {
+        match self.epoch_credits_len().checked_sub(1) {
+            Some(last) => {
+                let entry = last * EPOCH_CREDITS_ENTRY_SIZE;
+                u64::from_le_bytes(
+                    self.epoch_credits[entry + 8..entry + 16]
+                        .try_into()
+                        .unwrap(),
+                )
+            }
+            None => 0,
+        }
+    }
+}
+
+/// Reads a bincode `u64` collection length and advances `offset` past it
+fn read_len(data: &[u8], offset: &mut usize) -> Result<usize, ProgramError> {
+    let len = data
+        .get(*offset..offset.saturating_add(8))
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidAccountData)?;
+    *offset += 8;
+
+    usize::try_from(len).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Returns the offset after `count` items of `item_size` bytes starting at
+/// `offset`, failing if they do not fit in `data`
+fn skip(data: &[u8], offset: usize, count: usize, item_size: usize) -> Result<usize, ProgramError> {
+    count
+        .checked_mul(item_size)
+        .and_then(|size| offset.checked_add(size))
+        .filter(|end| *end <= data.len())
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+pub fn get_vote_state(vote_account_info: &AccountInfo) -> Result<VoteStateView<'_>, ProgramError> {
+    if !vote_account_info.is_owned_by(&VOTE_PROGRAM_ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // SAFETY: vote accounts are owned by the vote program, so they can never be
+    // one of the stake accounts this program borrows mutably
+    VoteStateView::from_bytes(unsafe { vote_account_info.borrow_data_unchecked() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VoteStateView;
+    use solana_sdk::{
+        pubkey::Pubkey,
+        vote::state::{VoteInit, VoteState, VoteState1_14_11, VoteStateVersions},
+    };
+
+    fn vote_state_with_credits(epochs: u64) -> VoteState {
+        let mut vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey: Pubkey::new_unique(),
+                authorized_voter: Pubkey::new_unique(),
+                authorized_withdrawer: Pubkey::new_unique(),
+                commission: 5,
+            },
+            &Default::default(),
+        );
+        for slot in 0..40 {
+            vote_state.process_next_vote_slot(slot, 0, 0);
+        }
+        for epoch in 0..epochs {
+            vote_state.increment_credits(epoch, epoch * 10 + 1);
+        }
+        vote_state
+    }
+
+    fn serialize(versions: &VoteStateVersions) -> Vec<u8> {
+        let mut data = vec![0; VoteState::size_of()];
+        bincode::serialize_into(&mut data[..], versions).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_credits_current() {
+        let vote_state = vote_state_with_credits(7);
+        let data = serialize(&VoteStateVersions::new_current(vote_state.clone()));
+
+        let view = VoteStateView::from_bytes(&data).unwrap();
+        assert_eq!(view.epoch_credits_len(), vote_state.epoch_credits.len());
+        assert!(view
+            .epoch_credits()
+            .eq(vote_state.epoch_credits.iter().copied()));
+        assert_eq!(view.credits(), vote_state.credits());
+    }
+
+    #[test]
+    fn test_credits_v1_14_11() {
+        let vote_state = vote_state_with_credits(3);
+        let data = serialize(&VoteStateVersions::V1_14_11(Box::new(
+            VoteState1_14_11::from(vote_state.clone()),
+        )));
+
+        let view = VoteStateView::from_bytes(&data).unwrap();
+        assert_eq!(view.epoch_credits_len(), vote_state.epoch_credits.len());
+        assert_eq!(view.credits(), vote_state.credits());
+    }
+
+    #[test]
+    fn test_no_credits() {
+        let mut vote_state = vote_state_with_credits(0);
+        vote_state.epoch_credits.clear();
+        let data = serialize(&VoteStateVersions::new_current(vote_state));
+
+        assert_eq!(VoteStateView::from_bytes(&data).unwrap().credits(), 0);
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        let data = serialize(&VoteStateVersions::new_current(vote_state_with_credits(2)));
+
+        assert!(VoteStateView::from_bytes(&[]).is_err());
+        assert!(VoteStateView::from_bytes(&data[..100]).is_err());
+
+        let mut bad_version = data.clone();
+        bad_version[0] = 7;
+        assert!(VoteStateView::from_bytes(&bad_version).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! Warmup/cooldown rate limiting without floating point.
+//!
+//! Each epoch, native (de)activates
+//! `((stake as f64 / cluster_stake as f64) * (cluster_effective as f64 * rate)) as u64`
+//! lamports of a delegation. Every one of those operations rounds to the nearest
+//! `f64`, and the roundings show up in the result, so exact rational arithmetic
+//! drifts from native by a few lamports. The same operations are instead carried
+//! out here on an integer significand and exponent, rounding half to even like
+//! IEEE 754 does. The results are bit-exact with native, without the soft-float
+//! routines SBF needs for `f64`.
+
+use super::Epoch;
+use crate::consts::{DEFAULT_WARMUP_COOLDOWN_RATE, NEW_WARMUP_COOLDOWN_RATE};
+
+/// Bits of an `f64` significand, implicit leading bit included
+const SIGNIFICAND_BITS: u32 = 53;
+
+/// Non-negative finite `f64`, valued `significand * 2^exponent`, where
+/// `significand` is either 0 or in `[2^52, 2^53)`
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Float {
+    significand: u64,
+    exponent: i32,
+}
+
+impl Float {
+    const ZERO: Self = Self {
+        significand: 0,
+        exponent: 0,
+    };
+
+    /// Decodes a positive normal `f64`, meant for constants
+    const fn from_f64(value: f64) -> Self {
+        let bits = value.to_bits();
+        let biased_exponent = ((bits >> 52) & 0x7ff) as i32;
+
+        Self {
+            significand: (bits & ((1 << 52) - 1)) | (1 << 52),
+            exponent: biased_exponent - 1075,
+        }
+    }
+
+    /// `value as f64`
+    fn from_u64(value: u64) -> Self {
+        if value == 0 {
+            return Self::ZERO;
+        }
+
+        Self::round(u128::from(value), 0, false)
+    }
+
+    /// Rounds the non-zero `value * 2^exponent` to the nearest `Float`, ties to
+    /// even. `sticky` tells whether non-zero bits below `value` were already
+    /// discarded, in which case `value` must have more than 53 bits.
+    fn round(value: u128, exponent: i32, sticky: bool) -> Self {
+        let bits = u128::BITS - value.leading_zeros();
+
+        if bits <= SIGNIFICAND_BITS {
+            let shift = SIGNIFICAND_BITS - bits;
+            return Self {
+                significand: (value << shift) as u64,
+                exponent: exponent - shift as i32,
+            };
+        }
+
+        let shift = bits - SIGNIFICAND_BITS;
+        let mut significand = value >> shift;
+        let mut exponent = exponent + shift as i32;
+
+        let remainder = value & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if remainder > half || (remainder == half && (sticky || significand & 1 == 1)) {
+            significand += 1;
+
+            if significand == 1 << SIGNIFICAND_BITS {
+                significand >>= 1;
+                exponent += 1;
+            }
+        }
+
+        Self {
+            significand: significand as u64,
+            exponent,
+        }
+    }
+
+    /// `self * other`
+    fn mul(self, other: Self) -> Self {
+        if self.significand == 0 || other.significand == 0 {
+            return Self::ZERO;
+        }
+
+        Self::round(
+            u128::from(self.significand) * u128::from(other.significand),
+            self.exponent + other.exponent,
+            false,
+        )
+    }
+
+    /// `self / other`, where `other` is not zero
+    fn div(self, other: Self) -> Self {
+        if self.significand == 0 {
+            return Self::ZERO;
+        }
+
+        // keeps 55 or 56 quotient bits: the 53 of the result, one to round with
+        // and at least one more, so that the remainder only matters as sticky
+        const EXTRA_BITS: u32 = 55;
+        let numerator = u128::from(self.significand) << EXTRA_BITS;
+        let denominator = u128::from(other.significand);
+
+        Self::round(
+            numerator / denominator,
+            self.exponent - other.exponent - EXTRA_BITS as i32,
+            numerator % denominator != 0,
+        )
+    }
+
+    /// `self as u64`, which truncates and saturates
+    fn to_u64(self) -> u64 {
+        if self.exponent >= 0 {
+            // the significand has 53 bits, so it fits with up to 11 more
+            if self.exponent > 11 {
+                u64::MAX
+            } else {
+                self.significand << self.exponent
+            }
+        } else if self.exponent <= -(SIGNIFICAND_BITS as i32) {
+            0
+        } else {
+            self.significand >> -self.exponent
+        }
+    }
+}
+
+// Means that no more than RATE of current effective stake may be added or subtracted per
+// epoch.
+fn warmup_cooldown_rate(current_epoch: Epoch, new_rate_activation_epoch: Option<Epoch>) -> Float {
+    const DEFAULT_RATE: Float = Float::from_f64(DEFAULT_WARMUP_COOLDOWN_RATE);
+    const NEW_RATE: Float = Float::from_f64(NEW_WARMUP_COOLDOWN_RATE);
+
+    if current_epoch < new_rate_activation_epoch.unwrap_or(Epoch::MAX) {
+        DEFAULT_RATE
+    } else {
+        NEW_RATE
+    }
+}
+
+/// Stake a delegation of `stake` lamports is entitled to (de)activate at
+/// `current_epoch`: its share `stake / cluster_stake` of the cluster's
+/// (de)activating stake, applied to the portion of `cluster_effective` the
+/// warmup/cooldown rate lets change.
+///
+/// `cluster_stake` must not be zero.
+pub fn rate_limited_stake_change(
+    stake: u64,
+    cluster_stake: u64,
+    cluster_effective: u64,
+    current_epoch: Epoch,
+    new_rate_activation_epoch: Option<Epoch>,
+) -> u64 {
+    let weight = Float::from_u64(stake).div(Float::from_u64(cluster_stake));
+    let rate = warmup_cooldown_rate(current_epoch, new_rate_activation_epoch);
+    let cluster_stake_change = Float::from_u64(cluster_effective).mul(rate);
+
+    weight.mul(cluster_stake_change).to_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Float {
+        fn to_f64(self) -> f64 {
+            self.significand as f64 * 2f64.powi(self.exponent)
+        }
+    }
+
+    /// xorshift64*, enough to spread test vectors over every magnitude
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        /// Uniform over bit lengths, so small and huge values are both common
+        fn next_u64(&mut self) -> u64 {
+            let bits = self.next() % 65;
+            match bits {
+                0 => 0,
+                64 => self.next(),
+                _ => self.next() & ((1 << bits) - 1),
+            }
+        }
+    }
+
+    const EDGE_VALUES: [u64; 12] = [
+        0,
+        1,
+        2,
+        3,
+        100,
+        300,
+        (1 << 53) - 1,
+        1 << 53,
+        (1 << 53) + 1,
+        (1 << 54) + 3,
+        u64::MAX - 1,
+        u64::MAX,
+    ];
+
+    fn native(
+        stake: u64,
+        cluster_stake: u64,
+        cluster_effective: u64,
+        current_epoch: u64,
+        new_rate_activation_epoch: Option<u64>,
+    ) -> u64 {
+        let rate = if current_epoch < new_rate_activation_epoch.unwrap_or(u64::MAX) {
+            DEFAULT_WARMUP_COOLDOWN_RATE
+        } else {
+            NEW_WARMUP_COOLDOWN_RATE
+        };
+        let weight = stake as f64 / cluster_stake as f64;
+        (weight * (cluster_effective as f64 * rate)) as u64
+    }
+
+    #[test]
+    fn test_from_f64() {
+        for value in [
+            DEFAULT_WARMUP_COOLDOWN_RATE,
+            NEW_WARMUP_COOLDOWN_RATE,
+            1.0,
+            1e300,
+        ] {
+            assert_eq!(Float::from_f64(value).to_f64(), value);
+        }
+    }
+
+    #[test]
+    fn test_float_ops_match_f64() {
+        let mut rng = Rng(0x5eed);
+        let values = EDGE_VALUES
+            .into_iter()
+            .chain((0..2_000).map(|_| rng.next_u64()));
+
+        let floats: Vec<(u64, Float)> = values.map(|v| (v, Float::from_u64(v))).collect();
+
+        for (value, float) in &floats {
+            assert_eq!(float.to_f64(), *value as f64, "{value} as f64");
+            assert_eq!(
+                float.to_u64(),
+                *value as f64 as u64,
+                "{value} as f64 as u64"
+            );
+        }
+
+        for (a, fa) in floats.iter().step_by(7) {
+            for (b, fb) in floats.iter().step_by(5) {
+                let (a, b) = (*a as f64, *b as f64);
+
+                assert_eq!(fa.mul(*fb).to_f64(), a * b, "{a} * {b}");
+                assert_eq!(fa.mul(*fb).to_u64(), (a * b) as u64, "{a} * {b}");
+                if b != 0.0 {
+                    assert_eq!(fa.div(*fb).to_f64(), a / b, "{a} / {b}");
+                    assert_eq!(fa.div(*fb).to_u64(), (a / b) as u64, "{a} / {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rate_limited_stake_change_matches_native() {
+        let mut rng = Rng(0xdead_beef);
+
+        let check = |stake, cluster_stake, cluster_effective| {
+            for (current_epoch, new_rate_activation_epoch) in
+                [(5, None), (5, Some(5)), (5, Some(6))]
+            {
+                assert_eq!(
+                    rate_limited_stake_change(
+                        stake,
+                        cluster_stake,
+                        cluster_effective,
+                        current_epoch.into(),
+                        new_rate_activation_epoch.map(Epoch::from),
+                    ),
+                    native(
+                        stake,
+                        cluster_stake,
+                        cluster_effective,
+                        current_epoch,
+                        new_rate_activation_epoch
+                    ),
+                    "{stake} {cluster_stake} {cluster_effective}"
+                );
+            }
+        };
+
+        for stake in EDGE_VALUES {
+            for cluster_stake in EDGE_VALUES.into_iter().filter(|v| *v != 0) {
+                for cluster_effective in EDGE_VALUES {
+                    check(stake, cluster_stake, cluster_effective);
+                }
+            }
+        }
+
+        for _ in 0..20_000 {
+            let cluster_stake = rng.next_u64().max(1);
+            check(rng.next_u64(), cluster_stake, rng.next_u64());
+        }
+
+        // realistic magnitudes, where the account is part of the cluster stake
+        for _ in 0..20_000 {
+            let cluster_stake = rng.next() % 600_000_000_000_000_000 + 1;
+            let stake = rng.next() % cluster_stake + 1;
+            check(stake, cluster_stake, rng.next() % 600_000_000_000_000_000);
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Random cases: a small world of accounts and instructions over it.
+//!
+//! Every key comes from a fixed pool, so instructions keep hitting accounts
+//! earlier ones changed, and amounts cluster around the balances and reserves
+//! where the processors branch.
+
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, LockupArgs},
+        stake_flags::StakeFlags,
+        state::{Authorized, Delegation, Lockup, Meta, Stake, StakeAuthorize, StakeStateV2},
+    },
+    stake_history::{StakeHistory, StakeHistoryEntry},
+};
+
+use crate::fixtures::*;
+
+pub const SEED: &str = "authority";
+
+/// xorshift64*
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must never be zero
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    pub fn chance(&mut self, one_in: u64) -> bool {
+        self.below(one_in) == 0
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.below(items.len() as u64) as usize]
+    }
+}
+
+/// One instruction and the sysvars it runs with
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub epoch: u64,
+    pub epoch_rewards_active: bool,
+    pub instruction: Instruction,
+}
+
+#[derive(Clone, Debug)]
+pub struct Case {
+    pub unix_timestamp: i64,
+    pub stake_history: StakeHistory,
+    pub accounts: Vec<(Pubkey, Account)>,
+    pub steps: Vec<Step>,
+}
+
+pub struct World {
+    pub stakes: [Pubkey; 4],
+    pub votes: [Pubkey; 2],
+    /// the last one is the usual lockup custodian
+    pub authorities: [Pubkey; 3],
+    /// derived from the first authority with `SEED`
+    pub seed_authority: Pubkey,
+    pub seed_owner: Pubkey,
+    pub recipient: Pubkey,
+}
+
+fn key(group: u8, index: u8) -> Pubkey {
+    let mut bytes = [0xd1; 32];
+    bytes[0] = group;
+    bytes[1] = index;
+    Pubkey::new_from_array(bytes)
+}
+
+impl World {
+    pub fn new() -> Self {
+        let authorities = [key(2, 0), key(2, 1), key(2, 2)];
+        let seed_owner = key(3, 0);
+
+        Self {
+            stakes: [key(0, 0), key(0, 1), key(0, 2), key(0, 3)],
+            votes: [key(1, 0), key(1, 1)],
+            authorities,
+            seed_authority: Pubkey::create_with_seed(&authorities[0], SEED, &seed_owner).unwrap(),
+            seed_owner,
+            recipient: key(4, 0),
+        }
+    }
+
+    /// Initial accounts and sysvars, with no steps yet
+    pub fn random_case(&self, rng: &mut Rng, epoch: u64) -> Case {
+        let mut accounts = Vec::new();
+        for stake in self.stakes {
+            accounts.push((stake, self.random_stake_account(rng, epoch)));
+        }
+        for vote in self.votes {
+            accounts.push((vote, self.random_vote_account(rng, epoch)));
+        }
+        accounts.push((self.recipient, Account::default()));
+
+        Case {
+            unix_timestamp: rng.below(1_000) as i64,
+            stake_history: random_stake_history(rng, epoch),
+            accounts,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn random_step(&self, rng: &mut Rng, epoch: u64, accounts: &[(Pubkey, Account)]) -> Step {
+        let epoch = match rng.below(8) {
+            0 => epoch + 1,
+            // far enough for the history to run out
+            1 => epoch + rng.below(600),
+            _ => epoch,
+        };
+
+        let mut instruction = self.random_instruction(rng, accounts);
+        mutate(rng, &mut instruction);
+
+        Step {
+            epoch,
+            epoch_rewards_active: rng.chance(32),
+            instruction,
+        }
+    }
+
+    fn random_authority(&self, rng: &mut Rng) -> Pubkey {
+        match rng.below(8) {
+            0 => self.seed_authority,
+            1 => rng.pick(&self.stakes),
+            _ => rng.pick(&self.authorities),
+        }
+    }
+
+    fn random_meta(&self, rng: &mut Rng, epoch: u64) -> Meta {
+        let lockup = if rng.chance(2) {
+            Lockup::default()
+        } else {
+            Lockup {
+                unix_timestamp: rng.below(1_000) as i64,
+                epoch: epoch.saturating_sub(2) + rng.below(5),
+                custodian: if rng.chance(4) {
+                    self.random_authority(rng)
+                } else {
+                    self.authorities[2]
+                },
+            }
+        };
+
+        Meta {
+            rent_exempt_reserve: if rng.chance(8) {
+                random_lamports(rng)
+            } else {
+                rent_exempt_reserve()
+            },
+            authorized: Authorized {
+                staker: self.random_authority(rng),
+                withdrawer: self.random_authority(rng),
+            },
+            lockup,
+        }
+    }
+
+    fn random_stake_account(&self, rng: &mut Rng, epoch: u64) -> Account {
+        let reserve = rent_exempt_reserve();
+        let lamports = reserve + random_lamports(rng);
+
+        let mut account = match rng.below(4) {
+            0 => uninitialized_account(rng.pick(&[0, reserve, lamports])),
+            1 => initialized_account(self.random_meta(rng, epoch), lamports),
+            _ => {
+                let activation_epoch = if rng.chance(16) {
+                    u64::MAX
+                } else {
+                    rng.below(epoch + 1)
+                };
+                let deactivation_epoch = if rng.chance(2) || activation_epoch == u64::MAX {
+                    u64::MAX
+                } else {
+                    activation_epoch + rng.below(epoch + 2 - activation_epoch)
+                };
+                let stake = Stake {
+                    delegation: Delegation {
+                        voter_pubkey: rng.pick(&self.votes),
+                        stake: rng.below(lamports - reserve + 1),
+                        activation_epoch,
+                        deactivation_epoch,
+                        ..Delegation::default()
+                    },
+                    credits_observed: rng.below(100),
+                };
+                // only the disabled `Redelegate` sets the must-fully-activate
+                // flag, and the native build ignores it, so it stays clear
+                stake_account(
+                    &StakeStateV2::Stake(self.random_meta(rng, epoch), stake, StakeFlags::empty()),
+                    lamports,
+                )
+            }
+        };
+
+        if rng.chance(16) {
+            match rng.below(3) {
+                0 => account.owner = self.recipient,
+                1 => account.data.truncate(STAKE_ACCOUNT_SIZE - 1),
+                _ => account.data[0] = 4,
+            }
+        }
+        account
+    }
+
+    fn random_vote_account(&self, rng: &mut Rng, epoch: u64) -> Account {
+        let last_epoch = match rng.below(3) {
+            0 => epoch,
+            1 => epoch.saturating_sub(5),
+            _ => rng.below(epoch + 1),
+        };
+        let epochs = rng.below(8).min(last_epoch + 1);
+
+        let mut epoch_credits = Vec::new();
+        let mut credits = 0;
+        for epoch in last_epoch + 1 - epochs..=last_epoch {
+            // gaps in voting
+            if rng.chance(8) {
+                continue;
+            }
+            let previous_credits = credits;
+            credits += rng.below(1_000);
+            epoch_credits.push((epoch, credits, previous_credits));
+        }
+
+        let mut account = vote_account(&epoch_credits);
+        if rng.chance(16) {
+            account.owner = self.recipient;
+        }
+        account
+    }
+
+    #[allow(deprecated)]
+    fn random_instruction(&self, rng: &mut Rng, accounts: &[(Pubkey, Account)]) -> Instruction {
+        let stake = rng.pick(&self.stakes);
+        let other = rng.pick(&self.stakes);
+        let vote = rng.pick(&self.votes);
+        let authority = self.random_authority(rng);
+        let new_authority = self.random_authority(rng);
+        let custodian = rng.chance(2).then_some(&self.authorities[2]);
+        let stake_authorize = rng.pick(&[StakeAuthorize::Staker, StakeAuthorize::Withdrawer]);
+        let seed = if rng.chance(8) { "other" } else { SEED };
+
+        let balance = accounts
+            .iter()
+            .find(|(key, _)| *key == stake)
+            .map_or(0, |(_, account)| account.lamports);
+        let lamports = random_amount(rng, balance);
+
+        match rng.below(18) {
+            0 => {
+                let meta = self.random_meta(rng, 0);
+                ixn::initialize(&stake, &meta.authorized, &meta.lockup)
+            }
+            1 => ixn::authorize(
+                &stake,
+                &authority,
+                &new_authority,
+                stake_authorize,
+                custodian,
+            ),
+            2 => ixn::delegate_stake(&stake, &authority, &vote),
+            3 => ixn::split(&stake, &authority, lamports, &other)
+                .pop()
+                .unwrap(),
+            4 => {
+                let to = if rng.chance(2) { self.recipient } else { other };
+                ixn::withdraw(&stake, &authority, &to, lamports, custodian)
+            }
+            5 => ixn::deactivate_stake(&stake, &authority),
+            6 => ixn::set_lockup(&stake, &random_lockup_args(rng, self), &authority),
+            7 => ixn::merge(&stake, &other, &authority).pop().unwrap(),
+            8 => ixn::authorize_with_seed(
+                &stake,
+                &self.authorities[0],
+                seed.to_string(),
+                &self.seed_owner,
+                &new_authority,
+                stake_authorize,
+                custodian,
+            ),
+            9 => ixn::initialize_checked(
+                &stake,
+                &Authorized {
+                    staker: authority,
+                    withdrawer: new_authority,
+                },
+            ),
+            10 => ixn::authorize_checked(
+                &stake,
+                &authority,
+                &new_authority,
+                stake_authorize,
+                custodian,
+            ),
+            11 => ixn::authorize_checked_with_seed(
+                &stake,
+                &self.authorities[0],
+                seed.to_string(),
+                &self.seed_owner,
+                &new_authority,
+                stake_authorize,
+                custodian,
+            ),
+            12 => ixn::set_lockup_checked(&stake, &random_lockup_args(rng, self), &authority),
+            13 => ixn::get_minimum_delegation(),
+            14 => ixn::deactivate_delinquent_stake(&stake, &vote, &rng.pick(&self.votes)),
+            15 => ixn::redelegate(&stake, &authority, &vote, &other)
+                .pop()
+                .unwrap(),
+            16 => ixn::move_stake(&stake, &other, &authority, lamports),
+            _ => ixn::move_lamports(&stake, &other, &authority, lamports),
+        }
+    }
+}
+
+fn random_lockup_args(rng: &mut Rng, world: &World) -> LockupArgs {
+    LockupArgs {
+        unix_timestamp: rng.chance(2).then(|| rng.below(1_000) as i64),
+        epoch: rng.chance(2).then(|| rng.below(1_000)),
+        custodian: rng.chance(2).then(|| world.random_authority(rng)),
+    }
+}
+
+/// Spread over every magnitude, small values most often
+fn random_lamports(rng: &mut Rng) -> u64 {
+    match rng.below(4) {
+        0 => rng.pick(&[0, 1, 2, MINIMUM_DELEGATION]),
+        _ => rng.next() >> (24 + rng.below(40)),
+    }
+}
+
+/// Amounts around the edges of what `balance` allows
+fn random_amount(rng: &mut Rng, balance: u64) -> u64 {
+    let reserve = rent_exempt_reserve();
+    match rng.below(8) {
+        0 => balance,
+        1 => balance.saturating_sub(1),
+        2 => balance + 1,
+        3 => balance.saturating_sub(reserve),
+        4 => balance / 2,
+        5 => reserve,
+        _ => random_lamports(rng),
+    }
+}
+
+fn random_stake_history(rng: &mut Rng, epoch: u64) -> StakeHistory {
+    let mut stake_history = StakeHistory::default();
+    if rng.chance(2) {
+        return stake_history;
+    }
+
+    for epoch in epoch.saturating_sub(rng.below(600))..epoch {
+        // a cluster with gaps in its history
+        if rng.chance(16) {
+            continue;
+        }
+        stake_history.add(
+            epoch,
+            StakeHistoryEntry {
+                effective: rng.next() >> rng.below(64),
+                activating: rng.next() >> (8 + rng.below(56)),
+                deactivating: rng.next() >> (8 + rng.below(56)),
+            },
+        );
+    }
+    stake_history
+}
+
+/// Malformed variants of an otherwise well formed instruction
+fn mutate(rng: &mut Rng, instruction: &mut Instruction) {
+    let accounts = instruction.accounts.len() as u64;
+    if accounts > 0 && rng.chance(8) {
+        let meta = &mut instruction.accounts[rng.below(accounts) as usize];
+        meta.is_signer = !meta.is_signer;
+    }
+    if accounts > 0 && rng.chance(16) {
+        let meta = &mut instruction.accounts[rng.below(accounts) as usize];
+        meta.is_writable = !meta.is_writable;
+    }
+    if rng.chance(16) {
+        instruction.accounts.pop();
+    }
+    if rng.chance(16) {
+        let len = rng.below(instruction.data.len() as u64 + 1);
+        instruction.data.truncate(len as usize);
+    }
+    if rng.chance(32) {
+        instruction.data.push(rng.next() as u8);
+    }
+}
@@ -0,0 +1,245 @@
+//! Differential tests against the native stake program.
+//!
+//! Random instruction sequences run through this program and the BPF build of
+//! the native one, see `Env::reference`. After every instruction the result,
+//! return data and accounts of both must be identical byte for byte. The first
+//! divergence is minimised and reported as a reproducer.
+//!
+//! Run with `cargo build-sbf && cargo test --features test-sbf --test differential`;
+//! the native program is checked in at `tests/elfs/solana_stake_program.so`.
+//! `DIFFERENTIAL_SEED` sets the seed of the first case and `DIFFERENTIAL_CASES`
+//! how many run.
+
+#![cfg(feature = "test-sbf")]
+
+#[path = "../mollusk/fixtures.rs"]
+mod fixtures;
+mod generate;
+mod report;
+
+use fixtures::Env;
+use generate::{Case, Rng, Step, World};
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    stake::{
+        instruction as ixn,
+        state::{Authorized, Lockup},
+    },
+};
+
+const MAX_STEPS: u64 = 8;
+
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    result: Result<(), InstructionError>,
+    return_data: Vec<u8>,
+    accounts: Vec<(Pubkey, Account)>,
+}
+
+/// Outcomes of the last step of a case
+pub struct Divergence {
+    ours: Outcome,
+    reference: Outcome,
+}
+
+struct Programs {
+    ours: Env,
+    reference: Env,
+}
+
+impl Programs {
+    fn new() -> Self {
+        Self {
+            ours: Env::new(),
+            reference: Env::reference(),
+        }
+    }
+
+    /// Runs `step` with both programs, updating `accounts` if they agree
+    fn process(
+        &mut self,
+        case: &Case,
+        step: &Step,
+        accounts: &mut [(Pubkey, Account)],
+    ) -> Option<Divergence> {
+        let ours = process(&mut self.ours, case, step, accounts);
+        let reference = process(&mut self.reference, case, step, accounts);
+
+        if ours != reference {
+            return Some(Divergence { ours, reference });
+        }
+
+        for (key, account) in accounts.iter_mut() {
+            if let Some((_, resulting)) = ours.accounts.iter().find(|(k, _)| k == key) {
+                *account = resulting.clone();
+            }
+        }
+        None
+    }
+
+    /// Replays `case`, truncating it after the first step the programs
+    /// disagree on
+    fn replay(&mut self, case: &mut Case) -> Option<Divergence> {
+        let mut accounts = case.accounts.clone();
+        for (i, step) in case.steps.iter().enumerate() {
+            if let Some(divergence) = self.process(case, step, &mut accounts) {
+                case.steps.truncate(i + 1);
+                return Some(divergence);
+            }
+        }
+        None
+    }
+
+    /// Generates and runs the case of `seed`, returning it if the programs
+    /// diverged
+    fn run_random(&mut self, world: &World, seed: u64) -> Option<Case> {
+        let mut rng = Rng::new(seed);
+        let mut epoch = rng.below(32);
+        let mut case = world.random_case(&mut rng, epoch);
+
+        let mut accounts = case.accounts.clone();
+        for _ in 0..=rng.below(MAX_STEPS) {
+            let step = world.random_step(&mut rng, epoch, &accounts);
+            epoch = step.epoch;
+
+            let diverged = self.process(&case, &step, &mut accounts).is_some();
+            case.steps.push(step);
+            if diverged {
+                return Some(case);
+            }
+        }
+        None
+    }
+
+    /// Drops every step the divergence doesn't depend on
+    fn minimize(&mut self, mut case: Case) -> (Case, Divergence) {
+        let mut divergence = self
+            .replay(&mut case)
+            .expect("a divergence must replay deterministically");
+
+        let mut i = 0;
+        while i < case.steps.len() {
+            let mut candidate = case.clone();
+            candidate.steps.remove(i);
+            match self.replay(&mut candidate) {
+                Some(found) => {
+                    case = candidate;
+                    divergence = found;
+                }
+                None => i += 1,
+            }
+        }
+
+        (case, divergence)
+    }
+}
+
+fn process(env: &mut Env, case: &Case, step: &Step, accounts: &[(Pubkey, Account)]) -> Outcome {
+    let sysvars = &mut env.mollusk.sysvars;
+    sysvars.clock.epoch = step.epoch;
+    sysvars.clock.unix_timestamp = case.unix_timestamp;
+    sysvars.stake_history = case.stake_history.clone();
+    sysvars.epoch_rewards.active = step.epoch_rewards_active;
+
+    let accounts = env.with_missing_accounts(&step.instruction, accounts);
+    let result = env
+        .mollusk
+        .process_instruction(&step.instruction, &accounts);
+
+    Outcome {
+        result: result.raw_result,
+        return_data: result.return_data,
+        accounts: result.resulting_accounts,
+    }
+}
+
+fn env_var(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .map(|value| value.parse().expect("not a number"))
+        .unwrap_or(default)
+}
+
+#[test]
+fn test_random_instruction_sequences() {
+    let first_seed = env_var("DIFFERENTIAL_SEED", 0);
+    let cases = env_var("DIFFERENTIAL_CASES", 1_000);
+
+    let world = World::new();
+    let mut programs = Programs::new();
+
+    for seed in first_seed..first_seed + cases {
+        if let Some(case) = programs.run_random(&world, seed) {
+            let (case, divergence) = programs.minimize(case);
+            panic!("{}", report::report(&world, seed, &case, &divergence));
+        }
+    }
+}
+
+/// A fixed sequence, so a broken harness can't pass by diverging nowhere
+#[test]
+fn test_stake_lifecycle() {
+    let world = World::new();
+    let mut programs = Programs::new();
+
+    let [stake, split, ..] = world.stakes;
+    let [vote, ..] = world.votes;
+    let [authority, ..] = world.authorities;
+    let reserve = fixtures::rent_exempt_reserve();
+    let lamports = reserve + 1_000_000;
+
+    // the split destination comes with its reserve, and both halves must be
+    // inactive to merge
+    let instructions = [
+        ixn::initialize(&stake, &Authorized::auto(&authority), &Lockup::default()),
+        ixn::delegate_stake(&stake, &authority, &vote),
+        ixn::split(&stake, &authority, 500_000, &split)
+            .pop()
+            .unwrap(),
+        ixn::deactivate_stake(&stake, &authority),
+        ixn::deactivate_stake(&split, &authority),
+        ixn::merge(&stake, &split, &authority).pop().unwrap(),
+        ixn::withdraw(
+            &stake,
+            &authority,
+            &world.recipient,
+            lamports + reserve,
+            None,
+        ),
+    ];
+    let epochs = [0, 0, 1, 1, 1, 2, 2];
+
+    let mut case = Case {
+        unix_timestamp: 0,
+        stake_history: Default::default(),
+        accounts: vec![
+            (stake, fixtures::uninitialized_account(lamports)),
+            (split, fixtures::uninitialized_account(reserve)),
+            (vote, fixtures::vote_account(&[(0, 10, 0)])),
+            (world.recipient, Account::default()),
+        ],
+        steps: instructions
+            .into_iter()
+            .zip(epochs)
+            .map(|(instruction, epoch)| Step {
+                epoch,
+                epoch_rewards_active: false,
+                instruction,
+            })
+            .collect(),
+    };
+
+    if let Some(divergence) = programs.replay(&mut case) {
+        panic!("{}", report::report(&world, 0, &case, &divergence));
+    }
+
+    // the programs agree, make sure it's not on failing every step
+    let mut accounts = case.accounts.clone();
+    for step in &case.steps {
+        let outcome = process(&mut programs.ours, &case, step, &accounts);
+        assert_eq!(outcome.result, Ok(()), "{:?}", step.instruction);
+        programs.process(&case, step, &mut accounts);
+    }
+    assert_eq!(accounts[0].1.lamports, 0);
+}
@@ -0,0 +1,173 @@
+//! Human readable reproducers, naming accounts by their role in the world.
+
+use std::fmt::Write;
+
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    stake::{self, instruction::StakeInstruction, state::StakeStateV2},
+    sysvar,
+};
+
+use crate::{
+    fixtures::STAKE_ACCOUNT_SIZE,
+    generate::{Case, World},
+    Divergence, Outcome,
+};
+
+impl World {
+    fn name(&self, key: &Pubkey) -> String {
+        let position = |keys: &[Pubkey]| keys.iter().position(|k| k == key);
+
+        if let Some(i) = position(&self.stakes) {
+            format!("stake{i}")
+        } else if let Some(i) = position(&self.votes) {
+            format!("vote{i}")
+        } else if let Some(i) = position(&self.authorities) {
+            format!("authority{i}")
+        } else if *key == self.seed_authority {
+            "seed_authority".to_string()
+        } else if *key == self.recipient {
+            "recipient".to_string()
+        } else if *key == sysvar::clock::ID {
+            "clock".to_string()
+        } else if *key == sysvar::rent::ID {
+            "rent".to_string()
+        } else if *key == sysvar::stake_history::ID {
+            "stake_history".to_string()
+        } else if *key == stake::config::ID {
+            "stake_config".to_string()
+        } else {
+            key.to_string()
+        }
+    }
+
+    fn describe_account(&self, account: &Account) -> String {
+        let state = (account.owner == stake::program::ID
+            && account.data.len() == STAKE_ACCOUNT_SIZE)
+            .then(|| bincode::deserialize::<StakeStateV2>(&account.data).ok())
+            .flatten();
+
+        match state {
+            Some(state) => format!("{} lamports, {state:?}", account.lamports),
+            None => format!(
+                "{} lamports, owner {}, data {:?}",
+                account.lamports,
+                self.name(&account.owner),
+                account.data
+            ),
+        }
+    }
+
+    fn describe_instruction(&self, instruction: &Instruction) -> String {
+        let mut out = match bincode::deserialize::<StakeInstruction>(&instruction.data) {
+            Ok(decoded) if bincode::serialize(&decoded).unwrap() == instruction.data => {
+                format!("{decoded:?}")
+            }
+            _ => format!("data {:?}", instruction.data),
+        };
+
+        out.push_str(" [");
+        for (i, meta) in instruction.accounts.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            out.push_str(&self.name(&meta.pubkey));
+            if meta.is_signer {
+                out.push_str(" signer");
+            }
+            if meta.is_writable {
+                out.push_str(" writable");
+            }
+        }
+        out.push(']');
+        out
+    }
+}
+
+/// The last step of `case` is where the programs diverged
+pub fn report(world: &World, seed: u64, case: &Case, divergence: &Divergence) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "programs diverged, rerun with DIFFERENTIAL_SEED={seed} DIFFERENTIAL_CASES=1\n"
+    )
+    .unwrap();
+
+    writeln!(out, "unix timestamp: {}", case.unix_timestamp).unwrap();
+    writeln!(out, "stake history: {:?}\n", case.stake_history).unwrap();
+
+    writeln!(out, "accounts:").unwrap();
+    for (key, account) in &case.accounts {
+        let used = case.steps.iter().any(|step| {
+            step.instruction
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == *key)
+        });
+        if used {
+            writeln!(
+                out,
+                "  {}: {}",
+                world.name(key),
+                world.describe_account(account)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(out, "\nsteps:").unwrap();
+    for (i, step) in case.steps.iter().enumerate() {
+        writeln!(
+            out,
+            "  {i}: epoch {}{}, {}",
+            step.epoch,
+            if step.epoch_rewards_active {
+                ", epoch rewards active"
+            } else {
+                ""
+            },
+            world.describe_instruction(&step.instruction)
+        )
+        .unwrap();
+    }
+
+    let Divergence { ours, reference } = divergence;
+    writeln!(
+        out,
+        "\nresult:\n  ours:   {:?}\n  native: {:?}",
+        ours.result, reference.result
+    )
+    .unwrap();
+    if ours.return_data != reference.return_data {
+        writeln!(
+            out,
+            "return data:\n  ours:   {:?}\n  native: {:?}",
+            ours.return_data, reference.return_data
+        )
+        .unwrap();
+    }
+    for (ours, reference) in accounts_that_differ(ours, reference) {
+        writeln!(
+            out,
+            "{}:\n  ours:   {}\n  native: {}",
+            world.name(&ours.0),
+            world.describe_account(&ours.1),
+            world.describe_account(&reference.1)
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+fn accounts_that_differ<'a>(
+    ours: &'a Outcome,
+    reference: &'a Outcome,
+) -> impl Iterator<Item = (&'a (Pubkey, Account), &'a (Pubkey, Account))> {
+    ours.accounts
+        .iter()
+        .zip(&reference.accounts)
+        .filter(|(ours, reference)| ours != reference)
+}
@@ -0,0 +1,324 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        state::{Authorized, Lockup, Meta, StakeAuthorize, StakeStateV2},
+    },
+};
+
+use crate::fixtures::*;
+
+/// staker and withdrawer are distinct, the lockup is in force until epoch 1
+fn locked_meta(staker: &Pubkey, withdrawer: &Pubkey, custodian: &Pubkey) -> Meta {
+    Meta {
+        authorized: Authorized {
+            staker: *staker,
+            withdrawer: *withdrawer,
+        },
+        lockup: Lockup {
+            unix_timestamp: 0,
+            epoch: 1,
+            custodian: *custodian,
+        },
+        ..meta(staker)
+    }
+}
+
+struct Setup {
+    env: Env,
+    stake: Pubkey,
+    staker: Pubkey,
+    withdrawer: Pubkey,
+    custodian: Pubkey,
+    account: Account,
+}
+
+impl Setup {
+    fn new() -> Self {
+        let staker = Pubkey::new_unique();
+        let withdrawer = Pubkey::new_unique();
+        let custodian = Pubkey::new_unique();
+        let account = initialized_account(
+            locked_meta(&staker, &withdrawer, &custodian),
+            rent_exempt_reserve(),
+        );
+
+        Self {
+            env: Env::new(),
+            stake: Pubkey::new_unique(),
+            staker,
+            withdrawer,
+            custodian,
+            account,
+        }
+    }
+
+    fn authorized(&self, result: &mollusk_svm::result::InstructionResult) -> Authorized {
+        stake_state(result, &self.stake).authorized().unwrap()
+    }
+}
+
+#[test]
+fn test_authorize_staker() {
+    let setup = Setup::new();
+    let new_staker = Pubkey::new_unique();
+
+    // the staker and the withdrawer can both change the staker
+    for authority in [setup.staker, setup.withdrawer] {
+        let instruction = ixn::authorize(
+            &setup.stake,
+            &authority,
+            &new_staker,
+            StakeAuthorize::Staker,
+            None,
+        );
+        let result = setup.env.process(
+            &instruction,
+            &[(setup.stake, setup.account.clone())],
+            &[Check::success()],
+        );
+
+        assert_eq!(
+            setup.authorized(&result),
+            Authorized {
+                staker: new_staker,
+                withdrawer: setup.withdrawer,
+            }
+        );
+    }
+}
+
+#[test]
+fn test_authorize_staker_missing_signature() {
+    let setup = Setup::new();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_withdrawer() {
+    let mut setup = Setup::new();
+    setup.env.mollusk.sysvars.clock.epoch = 1;
+    let new_withdrawer = Pubkey::new_unique();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.withdrawer,
+        &new_withdrawer,
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    let result = setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account.clone())],
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        setup.authorized(&result),
+        Authorized {
+            staker: setup.staker,
+            withdrawer: new_withdrawer,
+        }
+    );
+}
+
+#[test]
+fn test_authorize_withdrawer_by_staker() {
+    let mut setup = Setup::new();
+    setup.env.mollusk.sysvars.clock.epoch = 1;
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.staker,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_withdrawer_custodian_missing() {
+    let setup = Setup::new();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.withdrawer,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account.clone())],
+        &[Check::err(StakeError::CustodianMissing.into())],
+    );
+
+    // a custodian that didn't sign is ignored
+    let mut instruction = ixn::authorize(
+        &setup.stake,
+        &setup.withdrawer,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        Some(&setup.custodian),
+    );
+    instruction.accounts[3] = AccountMeta::new_readonly(setup.custodian, false);
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(StakeError::CustodianMissing.into())],
+    );
+}
+
+#[test]
+fn test_authorize_withdrawer_wrong_custodian() {
+    let setup = Setup::new();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.withdrawer,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        Some(&Pubkey::new_unique()),
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(StakeError::LockupInForce.into())],
+    );
+}
+
+#[test]
+fn test_authorize_withdrawer_with_custodian() {
+    let setup = Setup::new();
+    let new_withdrawer = Pubkey::new_unique();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.withdrawer,
+        &new_withdrawer,
+        StakeAuthorize::Withdrawer,
+        Some(&setup.custodian),
+    );
+    let result = setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account.clone())],
+        &[Check::success()],
+    );
+
+    assert_eq!(setup.authorized(&result).withdrawer, new_withdrawer);
+}
+
+#[test]
+fn test_authorize_uninitialized() {
+    let setup = Setup::new();
+    let authority = Pubkey::new_unique();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &authority,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, uninitialized_account(rent_exempt_reserve()))],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_authorize_wrong_owner() {
+    let setup = Setup::new();
+    let account = Account {
+        owner: Pubkey::new_unique(),
+        ..setup.account
+    };
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.staker,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, account)],
+        &[Check::err(ProgramError::InvalidAccountOwner)],
+    );
+}
+
+#[test]
+fn test_authorize_invalid_authority_type() {
+    let setup = Setup::new();
+
+    let mut instruction = ixn::authorize(
+        &setup.stake,
+        &setup.staker,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    // the `StakeAuthorize` tag follows the 4 byte discriminator and new authority
+    instruction.data[36] = 2;
+
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_authorize_keeps_stake_state() {
+    let setup = Setup::new();
+    let voter = Pubkey::new_unique();
+    let meta = locked_meta(&setup.staker, &setup.withdrawer, &setup.custodian);
+    let delegation = stake(&voter, 1_000, 0);
+    let new_staker = Pubkey::new_unique();
+
+    let instruction = ixn::authorize(
+        &setup.stake,
+        &setup.staker,
+        &new_staker,
+        StakeAuthorize::Staker,
+        None,
+    );
+    let result = setup.env.process(
+        &instruction,
+        &[(
+            setup.stake,
+            delegated_account(meta, delegation, rent_exempt_reserve() + 1_000),
+        )],
+        &[Check::success()],
+    );
+
+    let StakeStateV2::Stake(new_meta, new_stake, _) = stake_state(&result, &setup.stake) else {
+        panic!("not a delegated stake account");
+    };
+    assert_eq!(new_meta.authorized.staker, new_staker);
+    assert_eq!(new_meta.lockup, meta.lockup);
+    assert_eq!(new_stake, delegation);
+}
@@ -0,0 +1,171 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        state::{Authorized, Lockup, Meta, StakeAuthorize},
+    },
+};
+
+use crate::fixtures::*;
+
+fn setup() -> (Pubkey, Pubkey, Pubkey) {
+    (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    )
+}
+
+fn account(staker: &Pubkey, withdrawer: &Pubkey, lockup: Lockup) -> solana_sdk::account::Account {
+    initialized_account(
+        Meta {
+            authorized: Authorized {
+                staker: *staker,
+                withdrawer: *withdrawer,
+            },
+            lockup,
+            ..meta(staker)
+        },
+        rent_exempt_reserve(),
+    )
+}
+
+#[test]
+fn test_authorize_checked() {
+    let env = Env::new();
+    let (stake, staker, withdrawer) = setup();
+    let new_authority = Pubkey::new_unique();
+    let accounts = [(stake, account(&staker, &withdrawer, Lockup::default()))];
+
+    let instruction = ixn::authorize_checked(
+        &stake,
+        &withdrawer,
+        &new_authority,
+        StakeAuthorize::Staker,
+        None,
+    );
+    let result = env.process(&instruction, &accounts, &[Check::success()]);
+    assert_eq!(
+        stake_state(&result, &stake).authorized().unwrap(),
+        Authorized {
+            staker: new_authority,
+            withdrawer,
+        }
+    );
+
+    let instruction = ixn::authorize_checked(
+        &stake,
+        &withdrawer,
+        &new_authority,
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    let result = env.process(&instruction, &accounts, &[Check::success()]);
+    assert_eq!(
+        stake_state(&result, &stake).authorized().unwrap(),
+        Authorized {
+            staker,
+            withdrawer: new_authority,
+        }
+    );
+}
+
+#[test]
+fn test_authorize_checked_new_authority_not_signer() {
+    let env = Env::new();
+    let (stake, staker, withdrawer) = setup();
+    let new_authority = Pubkey::new_unique();
+
+    let mut instruction = ixn::authorize_checked(
+        &stake,
+        &staker,
+        &new_authority,
+        StakeAuthorize::Staker,
+        None,
+    );
+    instruction.accounts[3] = AccountMeta::new_readonly(new_authority, false);
+
+    env.process(
+        &instruction,
+        &[(stake, account(&staker, &withdrawer, Lockup::default()))],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_checked_withdrawer_by_staker() {
+    let env = Env::new();
+    let (stake, staker, withdrawer) = setup();
+
+    let instruction = ixn::authorize_checked(
+        &stake,
+        &staker,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    env.process(
+        &instruction,
+        &[(stake, account(&staker, &withdrawer, Lockup::default()))],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_checked_lockup() {
+    let env = Env::new();
+    let (stake, staker, withdrawer) = setup();
+    let custodian = Pubkey::new_unique();
+    let lockup = Lockup {
+        unix_timestamp: 0,
+        epoch: 1,
+        custodian,
+    };
+    let accounts = [(stake, account(&staker, &withdrawer, lockup))];
+
+    let instruction = ixn::authorize_checked(
+        &stake,
+        &withdrawer,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(StakeError::CustodianMissing.into())],
+    );
+
+    let instruction = ixn::authorize_checked(
+        &stake,
+        &withdrawer,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        Some(&custodian),
+    );
+    env.process(&instruction, &accounts, &[Check::success()]);
+}
+
+#[test]
+fn test_authorize_checked_not_enough_accounts() {
+    let env = Env::new();
+    let (stake, staker, withdrawer) = setup();
+
+    let mut instruction = ixn::authorize_checked(
+        &stake,
+        &staker,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    instruction.accounts.truncate(3);
+
+    env.process(
+        &instruction,
+        &[(stake, account(&staker, &withdrawer, Lockup::default()))],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
@@ -0,0 +1,196 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction as ixn,
+        state::{Authorized, StakeAuthorize},
+    },
+};
+
+use crate::fixtures::*;
+
+const SEED: &str = "authority";
+
+#[test]
+fn test_authorize_checked_with_seed() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let authority = Pubkey::create_with_seed(&base, SEED, &owner).unwrap();
+    let new_authority = Pubkey::new_unique();
+
+    let instruction = ixn::authorize_checked_with_seed(
+        &stake,
+        &base,
+        SEED.to_string(),
+        &owner,
+        &new_authority,
+        StakeAuthorize::Withdrawer,
+        None,
+    );
+    let result = env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&authority), rent_exempt_reserve()),
+        )],
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        stake_state(&result, &stake).authorized().unwrap(),
+        Authorized {
+            staker: authority,
+            withdrawer: new_authority,
+        }
+    );
+}
+
+#[test]
+fn test_authorize_checked_with_seed_new_authority_not_signer() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let authority = Pubkey::create_with_seed(&base, SEED, &owner).unwrap();
+    let new_authority = Pubkey::new_unique();
+
+    let mut instruction = ixn::authorize_checked_with_seed(
+        &stake,
+        &base,
+        SEED.to_string(),
+        &owner,
+        &new_authority,
+        StakeAuthorize::Staker,
+        None,
+    );
+    instruction.accounts[3] = AccountMeta::new_readonly(new_authority, false);
+
+    env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&authority), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_checked_with_seed_custodian_not_signer() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let authority = Pubkey::create_with_seed(&base, SEED, &owner).unwrap();
+    let custodian = Pubkey::new_unique();
+
+    // a custodian that is passed must sign, even when there is no lockup
+    let mut instruction = ixn::authorize_checked_with_seed(
+        &stake,
+        &base,
+        SEED.to_string(),
+        &owner,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        Some(&custodian),
+    );
+    instruction.accounts[4] = AccountMeta::new_readonly(custodian, false);
+
+    env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&authority), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_checked_with_seed_base_not_signer() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let authority = Pubkey::create_with_seed(&base, SEED, &owner).unwrap();
+
+    let mut instruction = ixn::authorize_checked_with_seed(
+        &stake,
+        &base,
+        SEED.to_string(),
+        &owner,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    instruction.accounts[1] = AccountMeta::new_readonly(base, false);
+
+    env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&authority), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_checked_with_seed_by_new_authority() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+
+    // the signing new authority counts as a signer, like in native, so an
+    // authority can hand its role over to itself without the derived address
+    let instruction = ixn::authorize_checked_with_seed(
+        &stake,
+        &base,
+        SEED.to_string(),
+        &owner,
+        &new_authority,
+        StakeAuthorize::Staker,
+        None,
+    );
+    env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&new_authority), rent_exempt_reserve()),
+        )],
+        &[Check::success()],
+    );
+}
+
+#[test]
+fn test_authorize_checked_with_seed_not_enough_accounts() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let base = Pubkey::new_unique();
+
+    let mut instruction = ixn::authorize_checked_with_seed(
+        &stake,
+        &base,
+        SEED.to_string(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        StakeAuthorize::Staker,
+        None,
+    );
+    instruction.accounts.truncate(3);
+
+    env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&base), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
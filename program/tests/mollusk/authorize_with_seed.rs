@@ -0,0 +1,257 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::{Pubkey, MAX_SEED_LEN},
+    stake::{
+        instruction as ixn,
+        state::{Authorized, Lockup, StakeAuthorize},
+    },
+};
+
+use crate::fixtures::*;
+
+const SEED: &str = "authority";
+
+struct Setup {
+    env: Env,
+    stake: Pubkey,
+    base: Pubkey,
+    owner: Pubkey,
+    account: Account,
+}
+
+impl Setup {
+    /// the stake account's staker and withdrawer are both derived from `base`
+    fn new() -> Self {
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let authority = Pubkey::create_with_seed(&base, SEED, &owner).unwrap();
+
+        Self {
+            env: Env::new(),
+            stake: Pubkey::new_unique(),
+            base,
+            owner,
+            account: initialized_account(meta(&authority), rent_exempt_reserve()),
+        }
+    }
+
+    fn instruction(
+        &self,
+        seed: &str,
+        owner: &Pubkey,
+        new_authority: &Pubkey,
+    ) -> solana_sdk::instruction::Instruction {
+        ixn::authorize_with_seed(
+            &self.stake,
+            &self.base,
+            seed.to_string(),
+            owner,
+            new_authority,
+            StakeAuthorize::Staker,
+            None,
+        )
+    }
+}
+
+#[test]
+fn test_authorize_with_seed() {
+    let setup = Setup::new();
+    let new_authority = Pubkey::new_unique();
+
+    for stake_authorize in [StakeAuthorize::Staker, StakeAuthorize::Withdrawer] {
+        let instruction = ixn::authorize_with_seed(
+            &setup.stake,
+            &setup.base,
+            SEED.to_string(),
+            &setup.owner,
+            &new_authority,
+            stake_authorize,
+            None,
+        );
+        let result = setup.env.process(
+            &instruction,
+            &[(setup.stake, setup.account.clone())],
+            &[Check::success()],
+        );
+
+        let authorized = stake_state(&result, &setup.stake).authorized().unwrap();
+        let expected = match stake_authorize {
+            StakeAuthorize::Staker => authorized.staker,
+            StakeAuthorize::Withdrawer => authorized.withdrawer,
+        };
+        assert_eq!(expected, new_authority);
+    }
+}
+
+#[test]
+fn test_authorize_with_seed_base_not_signer() {
+    let setup = Setup::new();
+
+    let mut instruction = setup.instruction(SEED, &setup.owner, &Pubkey::new_unique());
+    instruction.accounts[1] = AccountMeta::new_readonly(setup.base, false);
+
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_custodian_not_signer() {
+    let setup = Setup::new();
+    let custodian = Pubkey::new_unique();
+
+    // a custodian that is passed must sign, even when there is no lockup
+    let mut instruction = ixn::authorize_with_seed(
+        &setup.stake,
+        &setup.base,
+        SEED.to_string(),
+        &setup.owner,
+        &Pubkey::new_unique(),
+        StakeAuthorize::Withdrawer,
+        Some(&custodian),
+    );
+    instruction.accounts[3] = AccountMeta::new_readonly(custodian, false);
+
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_wrong_seed() {
+    let setup = Setup::new();
+
+    // the base signing is not enough, only the derived address is an authority
+    let instruction = setup.instruction("other", &setup.owner, &Pubkey::new_unique());
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_max_seed_length_exceeded() {
+    let setup = Setup::new();
+
+    let seed = "s".repeat(MAX_SEED_LEN + 1);
+    let instruction = setup.instruction(&seed, &setup.owner, &Pubkey::new_unique());
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::MaxSeedLengthExceeded)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_illegal_owner() {
+    let setup = Setup::new();
+
+    let mut owner = [1; 32];
+    owner[11..].copy_from_slice(b"ProgramDerivedAddress");
+    let instruction =
+        setup.instruction(SEED, &Pubkey::new_from_array(owner), &Pubkey::new_unique());
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::IllegalOwner)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_invalid_seed() {
+    let setup = Setup::new();
+
+    let mut instruction = setup.instruction(SEED, &setup.owner, &Pubkey::new_unique());
+    // discriminator, new authority, `StakeAuthorize` and the seed length precede the seed
+    instruction.data[48] = 0xff;
+
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account)],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_uninitialized() {
+    let setup = Setup::new();
+
+    let instruction = setup.instruction(SEED, &setup.owner, &Pubkey::new_unique());
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, uninitialized_account(rent_exempt_reserve()))],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_keeps_other_authority() {
+    let setup = Setup::new();
+    let authority = Pubkey::create_with_seed(&setup.base, SEED, &setup.owner).unwrap();
+    let new_authority = Pubkey::new_unique();
+
+    let instruction = setup.instruction(SEED, &setup.owner, &new_authority);
+    let result = setup.env.process(
+        &instruction,
+        &[(setup.stake, setup.account.clone())],
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.stake).authorized().unwrap(),
+        Authorized {
+            staker: new_authority,
+            withdrawer: authority,
+        }
+    );
+}
+
+#[test]
+fn test_authorize_with_seed_withdrawer_with_custodian() {
+    let setup = Setup::new();
+    let authority = Pubkey::create_with_seed(&setup.base, SEED, &setup.owner).unwrap();
+    let custodian = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+
+    let mut meta = meta(&authority);
+    meta.lockup = Lockup {
+        unix_timestamp: 0,
+        epoch: 1,
+        custodian,
+    };
+
+    // the custodian's signature lifts the lockup
+    let instruction = ixn::authorize_with_seed(
+        &setup.stake,
+        &setup.base,
+        SEED.to_string(),
+        &setup.owner,
+        &new_authority,
+        StakeAuthorize::Withdrawer,
+        Some(&custodian),
+    );
+    let result = setup.env.process(
+        &instruction,
+        &[(
+            setup.stake,
+            initialized_account(meta, rent_exempt_reserve()),
+        )],
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.stake)
+            .authorized()
+            .unwrap()
+            .withdrawer,
+        new_authority
+    );
+}
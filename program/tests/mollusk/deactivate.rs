@@ -0,0 +1,132 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        stake_flags::StakeFlags,
+        state::StakeStateV2,
+    },
+};
+
+use crate::fixtures::*;
+
+const STAKE_AMOUNT: u64 = 1_000_000;
+
+fn account(
+    staker: &Pubkey,
+    activation_epoch: u64,
+    deactivation_epoch: u64,
+    flags: StakeFlags,
+) -> Account {
+    let mut stake = stake(&Pubkey::new_unique(), STAKE_AMOUNT, activation_epoch);
+    stake.delegation.deactivation_epoch = deactivation_epoch;
+    stake_account(
+        &StakeStateV2::Stake(meta(staker), stake, flags),
+        rent_exempt_reserve() + STAKE_AMOUNT,
+    )
+}
+
+#[allow(deprecated)]
+fn must_fully_activate() -> StakeFlags {
+    StakeFlags::MUST_FULLY_ACTIVATE_BEFORE_DEACTIVATION_IS_PERMITTED
+}
+
+#[test]
+fn test_deactivate() {
+    let env = Env::at_epoch(10);
+    let stake = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+
+    // activating stake can be deactivated too
+    for activation_epoch in [0, 10] {
+        let result = env.process(
+            &ixn::deactivate_stake(&stake, &staker),
+            &[(
+                stake,
+                account(&staker, activation_epoch, u64::MAX, StakeFlags::empty()),
+            )],
+            &[Check::success()],
+        );
+
+        let StakeStateV2::Stake(_, new_stake, flags) = stake_state(&result, &stake) else {
+            panic!("not a delegated stake account");
+        };
+        assert_eq!(new_stake.delegation.activation_epoch, activation_epoch);
+        assert_eq!(new_stake.delegation.deactivation_epoch, 10);
+        assert_eq!(flags, StakeFlags::empty());
+    }
+}
+
+#[test]
+fn test_deactivate_already_deactivated() {
+    let env = Env::at_epoch(10);
+    let stake = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+
+    env.process(
+        &ixn::deactivate_stake(&stake, &staker),
+        &[(stake, account(&staker, 0, 5, StakeFlags::empty()))],
+        &[Check::err(StakeError::AlreadyDeactivated.into())],
+    );
+}
+
+#[test]
+fn test_deactivate_must_fully_activate() {
+    let env = Env::at_epoch(10);
+    let stake = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+
+    env.process(
+        &ixn::deactivate_stake(&stake, &staker),
+        &[(stake, account(&staker, 10, u64::MAX, must_fully_activate()))],
+        &[Check::err(
+            StakeError::RedelegatedStakeMustFullyActivateBeforeDeactivationIsPermitted.into(),
+        )],
+    );
+
+    // once fully active the flag is cleared on deactivation
+    let result = env.process(
+        &ixn::deactivate_stake(&stake, &staker),
+        &[(stake, account(&staker, 5, u64::MAX, must_fully_activate()))],
+        &[Check::success()],
+    );
+    let StakeStateV2::Stake(_, _, flags) = stake_state(&result, &stake) else {
+        panic!("not a delegated stake account");
+    };
+    assert_eq!(flags, StakeFlags::empty());
+}
+
+#[test]
+fn test_deactivate_missing_signature() {
+    let env = Env::at_epoch(10);
+    let stake = Pubkey::new_unique();
+
+    env.process(
+        &ixn::deactivate_stake(&stake, &Pubkey::new_unique()),
+        &[(
+            stake,
+            account(&Pubkey::new_unique(), 0, u64::MAX, StakeFlags::empty()),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_deactivate_not_delegated() {
+    let env = Env::at_epoch(10);
+    let stake = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+
+    for account in [
+        uninitialized_account(rent_exempt_reserve()),
+        initialized_account(meta(&staker), rent_exempt_reserve()),
+    ] {
+        env.process(
+            &ixn::deactivate_stake(&stake, &staker),
+            &[(stake, account)],
+            &[Check::err(ProgramError::InvalidAccountData)],
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::instruction::{self as ixn, StakeError},
+    stake::state::StakeStateV2,
+};
+
+use crate::fixtures::*;
+
+const EPOCH: u64 = 20;
+
+/// voted in each of the last five epochs
+fn reference_vote_account() -> Account {
+    vote_account(&[(16, 1, 0), (17, 2, 1), (18, 3, 2), (19, 4, 3), (20, 5, 4)])
+}
+
+struct Setup {
+    env: Env,
+    stake: Pubkey,
+    delinquent: Pubkey,
+    reference: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(EPOCH),
+            stake: Pubkey::new_unique(),
+            delinquent: Pubkey::new_unique(),
+            reference: Pubkey::new_unique(),
+        }
+    }
+
+    fn deactivate(
+        &self,
+        stake_account: Account,
+        delinquent_account: Account,
+        reference_account: Account,
+        checks: &[Check],
+    ) -> InstructionResult {
+        self.env.process(
+            &ixn::deactivate_delinquent_stake(&self.stake, &self.delinquent, &self.reference),
+            &[
+                (self.stake, stake_account),
+                (self.delinquent, delinquent_account),
+                (self.reference, reference_account),
+            ],
+            checks,
+        )
+    }
+
+    fn delegated(&self, voter: &Pubkey) -> Account {
+        delegated_account(
+            meta(&Pubkey::new_unique()),
+            stake(voter, 1_000, 0),
+            rent_exempt_reserve() + 1_000,
+        )
+    }
+}
+
+#[test]
+fn test_deactivate_delinquent() {
+    let setup = Setup::new();
+
+    // never voted, or last voted five epochs ago
+    for delinquent_account in [vote_account(&[]), vote_account(&[(15, 10, 0)])] {
+        let result = setup.deactivate(
+            setup.delegated(&setup.delinquent),
+            delinquent_account,
+            reference_vote_account(),
+            &[Check::success()],
+        );
+
+        let StakeStateV2::Stake(_, stake, _) = stake_state(&result, &setup.stake) else {
+            panic!("not a delegated stake account");
+        };
+        assert_eq!(stake.delegation.deactivation_epoch, EPOCH);
+    }
+}
+
+#[test]
+fn test_deactivate_delinquent_not_delinquent() {
+    let setup = Setup::new();
+
+    setup.deactivate(
+        setup.delegated(&setup.delinquent),
+        vote_account(&[(16, 10, 0)]),
+        reference_vote_account(),
+        &[Check::err(
+            StakeError::MinimumDelinquentEpochsForDeactivationNotMet.into(),
+        )],
+    );
+}
+
+#[test]
+fn test_deactivate_delinquent_insufficient_reference_votes() {
+    let setup = Setup::new();
+
+    for reference_account in [
+        vote_account(&[]),
+        // missed the current epoch
+        vote_account(&[(15, 1, 0), (16, 2, 1), (17, 3, 2), (18, 4, 3), (19, 5, 4)]),
+        // missed an epoch in between
+        vote_account(&[(15, 1, 0), (16, 2, 1), (18, 3, 2), (19, 4, 3), (20, 5, 4)]),
+    ] {
+        setup.deactivate(
+            setup.delegated(&setup.delinquent),
+            vote_account(&[]),
+            reference_account,
+            &[Check::err(StakeError::InsufficientReferenceVotes.into())],
+        );
+    }
+}
+
+#[test]
+fn test_deactivate_delinquent_vote_address_mismatch() {
+    let setup = Setup::new();
+
+    setup.deactivate(
+        setup.delegated(&Pubkey::new_unique()),
+        vote_account(&[]),
+        reference_vote_account(),
+        &[Check::err(StakeError::VoteAddressMismatch.into())],
+    );
+}
+
+#[test]
+fn test_deactivate_delinquent_already_deactivated() {
+    let setup = Setup::new();
+    let mut stake = stake(&setup.delinquent, 1_000, 0);
+    stake.delegation.deactivation_epoch = 19;
+
+    setup.deactivate(
+        delegated_account(
+            meta(&Pubkey::new_unique()),
+            stake,
+            rent_exempt_reserve() + 1_000,
+        ),
+        vote_account(&[]),
+        reference_vote_account(),
+        &[Check::err(StakeError::AlreadyDeactivated.into())],
+    );
+}
+
+#[test]
+fn test_deactivate_delinquent_not_delegated() {
+    let setup = Setup::new();
+
+    setup.deactivate(
+        initialized_account(meta(&Pubkey::new_unique()), rent_exempt_reserve()),
+        vote_account(&[]),
+        reference_vote_account(),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_deactivate_delinquent_not_a_vote_account() {
+    let setup = Setup::new();
+    let mut reference_account = reference_vote_account();
+    reference_account.owner = Pubkey::new_unique();
+
+    setup.deactivate(
+        setup.delegated(&setup.delinquent),
+        vote_account(&[]),
+        reference_account,
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
@@ -0,0 +1,191 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        stake_flags::StakeFlags,
+        state::{Delegation, Stake, StakeStateV2},
+    },
+};
+
+use crate::fixtures::*;
+
+const STAKE_AMOUNT: u64 = 1_000_000;
+
+struct Setup {
+    env: Env,
+    stake: Pubkey,
+    staker: Pubkey,
+    vote: Pubkey,
+    vote_account: Account,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            stake: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            vote: Pubkey::new_unique(),
+            vote_account: vote_account(&[(9, 100, 0)]),
+        }
+    }
+
+    fn delegate(&self, stake_account: Account, checks: &[Check]) -> StakeStateV2 {
+        let instruction = ixn::delegate_stake(&self.stake, &self.staker, &self.vote);
+        let result = self.env.process(
+            &instruction,
+            &[
+                (self.stake, stake_account),
+                (self.vote, self.vote_account.clone()),
+            ],
+            checks,
+        );
+        stake_state(&result, &self.stake)
+    }
+
+    fn delegated(&self, voter: &Pubkey, activation_epoch: u64, deactivation_epoch: u64) -> Account {
+        let mut stake = stake(voter, STAKE_AMOUNT, activation_epoch);
+        stake.delegation.deactivation_epoch = deactivation_epoch;
+        delegated_account(
+            meta(&self.staker),
+            stake,
+            rent_exempt_reserve() + STAKE_AMOUNT,
+        )
+    }
+}
+
+#[test]
+fn test_delegate() {
+    let setup = Setup::new();
+
+    let state = setup.delegate(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve() + STAKE_AMOUNT),
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        state,
+        StakeStateV2::Stake(
+            meta(&setup.staker),
+            Stake {
+                delegation: Delegation::new(&setup.vote, STAKE_AMOUNT, 10),
+                credits_observed: 100,
+            },
+            StakeFlags::empty(),
+        )
+    );
+}
+
+#[test]
+fn test_delegate_insufficient_delegation() {
+    let setup = Setup::new();
+
+    setup.delegate(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve()),
+        &[Check::err(StakeError::InsufficientDelegation.into())],
+    );
+}
+
+#[test]
+fn test_delegate_missing_signature() {
+    let setup = Setup::new();
+    let instruction = ixn::delegate_stake(&setup.stake, &Pubkey::new_unique(), &setup.vote);
+
+    setup.env.process(
+        &instruction,
+        &[
+            (
+                setup.stake,
+                initialized_account(meta(&setup.staker), rent_exempt_reserve() + STAKE_AMOUNT),
+            ),
+            (setup.vote, setup.vote_account.clone()),
+        ],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_delegate_not_a_vote_account() {
+    let mut setup = Setup::new();
+    setup.vote_account.owner = Pubkey::new_unique();
+
+    setup.delegate(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve() + STAKE_AMOUNT),
+        &[Check::err(ProgramError::IncorrectProgramId)],
+    );
+}
+
+#[test]
+fn test_delegate_invalid_vote_state() {
+    let mut setup = Setup::new();
+    setup.vote_account.data = vec![0xff; 4];
+
+    setup.delegate(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve() + STAKE_AMOUNT),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_delegate_uninitialized() {
+    let setup = Setup::new();
+
+    setup.delegate(
+        uninitialized_account(rent_exempt_reserve() + STAKE_AMOUNT),
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_redelegate_active_stake() {
+    let setup = Setup::new();
+
+    setup.delegate(
+        setup.delegated(&Pubkey::new_unique(), 0, u64::MAX),
+        &[Check::err(StakeError::TooSoonToRedelegate.into())],
+    );
+    // also while deactivating, unless it is to the same vote account
+    setup.delegate(
+        setup.delegated(&Pubkey::new_unique(), 0, 10),
+        &[Check::err(StakeError::TooSoonToRedelegate.into())],
+    );
+}
+
+#[test]
+fn test_redelegate_rescinds_deactivation() {
+    let setup = Setup::new();
+
+    let state = setup.delegate(setup.delegated(&setup.vote, 0, 10), &[Check::success()]);
+
+    let StakeStateV2::Stake(_, stake, _) = state else {
+        panic!("not a delegated stake account");
+    };
+    assert_eq!(stake.delegation.activation_epoch, 0);
+    assert_eq!(stake.delegation.deactivation_epoch, u64::MAX);
+    assert_eq!(stake.credits_observed, 0);
+}
+
+#[test]
+fn test_redelegate_inactive_stake() {
+    let setup = Setup::new();
+
+    // deactivated in an earlier epoch, so fully inactive without stake history
+    let state = setup.delegate(
+        setup.delegated(&Pubkey::new_unique(), 0, 5),
+        &[Check::success()],
+    );
+
+    let StakeStateV2::Stake(_, stake, _) = state else {
+        panic!("not a delegated stake account");
+    };
+    assert_eq!(
+        stake,
+        Stake {
+            delegation: Delegation::new(&setup.vote, STAKE_AMOUNT, 10),
+            credits_observed: 100,
+        }
+    );
+}
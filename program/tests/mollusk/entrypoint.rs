@@ -0,0 +1,75 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::Instruction,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        self,
+        instruction::{self as ixn, StakeError},
+    },
+};
+
+use crate::fixtures::*;
+
+#[test]
+fn test_invalid_discriminator() {
+    let env = Env::new();
+
+    for data in [
+        vec![],
+        vec![0; 3],
+        18u32.to_le_bytes().to_vec(),
+        vec![0xff; 4],
+    ] {
+        env.process(
+            &Instruction::new_with_bytes(stake::program::ID, &data, vec![]),
+            &[],
+            &[Check::err(ProgramError::InvalidInstructionData)],
+        );
+    }
+}
+
+#[test]
+fn test_epoch_rewards_active() {
+    let mut env = Env::at_epoch(10);
+    env.start_epoch_rewards();
+    let stake = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+    let account = delegated_account(
+        meta(&staker),
+        crate::fixtures::stake(&Pubkey::new_unique(), 1_000, 0),
+        rent_exempt_reserve() + 1_000,
+    );
+
+    env.process(
+        &ixn::deactivate_stake(&stake, &staker),
+        &[(stake, account)],
+        &[Check::err(StakeError::EpochRewardsActive.into())],
+    );
+
+    // checked after the instruction data is parsed, as in the native program
+    let mut instruction = ixn::withdraw(&stake, &staker, &Pubkey::new_unique(), 1, None);
+    instruction.data.truncate(4);
+    env.process(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+    let mut instruction = ixn::split(&stake, &staker, 1, &Pubkey::new_unique())[2].clone();
+    instruction.data.truncate(8);
+    env.process(
+        &instruction,
+        &[],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    // the one instruction allowed during the distribution
+    env.process(
+        &ixn::get_minimum_delegation(),
+        &[],
+        &[
+            Check::success(),
+            Check::return_data(&MINIMUM_DELEGATION.to_le_bytes()),
+        ],
+    );
+}
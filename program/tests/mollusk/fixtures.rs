@@ -0,0 +1,211 @@
+//! Accounts and runtime environment shared by the instruction tests.
+//!
+//! Stake accounts are built and read back through the native bincode types, so
+//! every test also checks that the program keeps the native account layout.
+
+// also included by the differential tests, which only use part of it
+#![allow(dead_code)]
+
+use mollusk_svm::{
+    result::{Check, InstructionResult},
+    Mollusk,
+};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    account::Account,
+    clock::Epoch,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    stake::{
+        self,
+        stake_flags::StakeFlags,
+        state::{Authorized, Delegation, Lockup, Meta, Stake, StakeStateV2},
+    },
+    stake_history::StakeHistory,
+    sysvar,
+    vote::state::{VoteInit, VoteState, VoteStateVersions},
+};
+
+const PROGRAM: &str = "target/deploy/solana_pinocchio_starter";
+
+/// BPF build of the native stake program, see `tests/elfs/README.md`
+const REFERENCE_PROGRAM: &str = "tests/elfs/solana_stake_program";
+
+/// sha256 of the pinned solana-program/stake 1.0.1 build
+const REFERENCE_PROGRAM_SHA256: &str =
+    "f35947e5e5b063b5339cd6e8a18a31b837c2edce6b8b8dd7e2762611f88f55c5";
+
+pub const STAKE_ACCOUNT_SIZE: usize = StakeStateV2::size_of();
+
+/// the program's minimum delegation, see `get_minimum_delegation`
+pub const MINIMUM_DELEGATION: u64 = 1;
+
+pub fn rent_exempt_reserve() -> u64 {
+    Rent::default().minimum_balance(STAKE_ACCOUNT_SIZE)
+}
+
+pub struct Env {
+    pub mollusk: Mollusk,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        let elf = format!("{PROGRAM}.so");
+        assert!(
+            std::path::Path::new(&elf).exists(),
+            "{elf} is missing, build the program with `cargo build-sbf` first"
+        );
+
+        Self::with_program(PROGRAM)
+    }
+
+    /// Runs the native stake program instead, the pinned build of
+    /// <https://github.com/solana-program/stake> checked in at
+    /// `tests/elfs/solana_stake_program.so`.
+    pub fn reference() -> Self {
+        let elf = format!("{REFERENCE_PROGRAM}.so");
+        let bytes = std::fs::read(&elf).unwrap_or_else(|e| panic!("{elf}: {e}"));
+        let sha256: String = Sha256::digest(&bytes)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        assert_eq!(
+            sha256, REFERENCE_PROGRAM_SHA256,
+            "{elf} is not the pinned native stake program, see tests/elfs/README.md"
+        );
+
+        Self::with_program(REFERENCE_PROGRAM)
+    }
+
+    fn with_program(program: &str) -> Self {
+        let mut mollusk = Mollusk::new(&stake::program::ID, program);
+        // with no history a delegation is fully effective from the epoch after
+        // it was made and fully inactive from the epoch after it was deactivated
+        mollusk.sysvars.stake_history = StakeHistory::default();
+        Self { mollusk }
+    }
+
+    pub fn at_epoch(epoch: Epoch) -> Self {
+        let mut env = Self::new();
+        env.mollusk.sysvars.clock.epoch = epoch;
+        env
+    }
+
+    pub fn set_unix_timestamp(&mut self, unix_timestamp: i64) {
+        self.mollusk.sysvars.clock.unix_timestamp = unix_timestamp;
+    }
+
+    pub fn start_epoch_rewards(&mut self) {
+        self.mollusk.sysvars.epoch_rewards.active = true;
+    }
+
+    /// Runs `instruction` and validates `checks`, see `with_missing_accounts`
+    pub fn process(
+        &self,
+        instruction: &Instruction,
+        accounts: &[(Pubkey, Account)],
+        checks: &[Check],
+    ) -> InstructionResult {
+        let accounts = self.with_missing_accounts(instruction, accounts);
+
+        self.mollusk
+            .process_and_validate_instruction(instruction, &accounts, checks)
+    }
+
+    /// `accounts` followed by an account for every key of `instruction` it lacks
+    pub fn with_missing_accounts(
+        &self,
+        instruction: &Instruction,
+        accounts: &[(Pubkey, Account)],
+    ) -> Vec<(Pubkey, Account)> {
+        let mut accounts = accounts.to_vec();
+        for meta in &instruction.accounts {
+            if !accounts.iter().any(|(key, _)| *key == meta.pubkey) {
+                accounts.push(self.default_account(&meta.pubkey));
+            }
+        }
+        accounts
+    }
+
+    /// Sysvar accounts come from the environment, any other account
+    /// (authorities, the stake config) is an empty system account
+    fn default_account(&self, key: &Pubkey) -> (Pubkey, Account) {
+        let sysvars = &self.mollusk.sysvars;
+        match *key {
+            sysvar::clock::ID => sysvars.keyed_account_for_clock_sysvar(),
+            sysvar::rent::ID => sysvars.keyed_account_for_rent_sysvar(),
+            sysvar::stake_history::ID => sysvars.keyed_account_for_stake_history_sysvar(),
+            sysvar::epoch_rewards::ID => sysvars.keyed_account_for_epoch_rewards_sysvar(),
+            _ => (*key, Account::default()),
+        }
+    }
+}
+
+pub fn stake_account(state: &StakeStateV2, lamports: u64) -> Account {
+    let mut data = vec![0; STAKE_ACCOUNT_SIZE];
+    bincode::serialize_into(&mut data[..], state).unwrap();
+
+    Account {
+        lamports,
+        data,
+        owner: stake::program::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn uninitialized_account(lamports: u64) -> Account {
+    stake_account(&StakeStateV2::Uninitialized, lamports)
+}
+
+pub fn initialized_account(meta: Meta, lamports: u64) -> Account {
+    stake_account(&StakeStateV2::Initialized(meta), lamports)
+}
+
+pub fn delegated_account(meta: Meta, stake: Stake, lamports: u64) -> Account {
+    stake_account(
+        &StakeStateV2::Stake(meta, stake, StakeFlags::empty()),
+        lamports,
+    )
+}
+
+/// `authority` is both staker and withdrawer, there is no lockup
+pub fn meta(authority: &Pubkey) -> Meta {
+    Meta {
+        rent_exempt_reserve: rent_exempt_reserve(),
+        authorized: Authorized::auto(authority),
+        lockup: Lockup::default(),
+    }
+}
+
+pub fn stake(voter: &Pubkey, amount: u64, activation_epoch: Epoch) -> Stake {
+    Stake {
+        delegation: Delegation::new(voter, amount, activation_epoch),
+        credits_observed: 0,
+    }
+}
+
+pub fn vote_account(epoch_credits: &[(Epoch, u64, u64)]) -> Account {
+    let mut vote_state = VoteState::new(&VoteInit::default(), &Default::default());
+    vote_state.epoch_credits = epoch_credits.to_vec();
+
+    let mut data = vec![0; VoteState::size_of()];
+    VoteState::serialize(&VoteStateVersions::new_current(vote_state), &mut data).unwrap();
+
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: solana_sdk::vote::program::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub fn stake_state(result: &InstructionResult, key: &Pubkey) -> StakeStateV2 {
+    bincode::deserialize(&result.get_account(key).unwrap().data).unwrap()
+}
+
+pub fn lamports(result: &InstructionResult, key: &Pubkey) -> u64 {
+    result.get_account(key).unwrap().lamports
+}
@@ -0,0 +1,18 @@
+use mollusk_svm::result::Check;
+use solana_sdk::stake::instruction as ixn;
+
+use crate::fixtures::*;
+
+#[test]
+fn test_get_minimum_delegation() {
+    let env = Env::new();
+
+    env.process(
+        &ixn::get_minimum_delegation(),
+        &[],
+        &[
+            Check::success(),
+            Check::return_data(&MINIMUM_DELEGATION.to_le_bytes()),
+        ],
+    );
+}
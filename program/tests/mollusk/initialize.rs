@@ -0,0 +1,141 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction as ixn,
+        state::{Authorized, Lockup, Meta, StakeStateV2},
+    },
+};
+
+use crate::fixtures::*;
+
+fn authorized() -> Authorized {
+    Authorized {
+        staker: Pubkey::new_unique(),
+        withdrawer: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn test_initialize() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let authorized = authorized();
+    let lockup = Lockup {
+        unix_timestamp: 1,
+        epoch: 2,
+        custodian: Pubkey::new_unique(),
+    };
+
+    let instruction = ixn::initialize(&stake, &authorized, &lockup);
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve()))];
+    let result = env.process(&instruction, &accounts, &[Check::success()]);
+
+    assert_eq!(
+        stake_state(&result, &stake),
+        StakeStateV2::Initialized(Meta {
+            rent_exempt_reserve: rent_exempt_reserve(),
+            authorized,
+            lockup,
+        })
+    );
+}
+
+#[test]
+fn test_initialize_insufficient_funds() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let instruction = ixn::initialize(&stake, &authorized(), &Lockup::default());
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve() - 1))];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+}
+
+#[test]
+fn test_initialize_already_initialized() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let instruction = ixn::initialize(&stake, &authorized(), &Lockup::default());
+    let accounts = [(
+        stake,
+        initialized_account(meta(&Pubkey::new_unique()), rent_exempt_reserve()),
+    )];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_initialize_wrong_size() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let mut account = uninitialized_account(rent_exempt_reserve() * 2);
+    account.data.push(0);
+
+    let instruction = ixn::initialize(&stake, &authorized(), &Lockup::default());
+    env.process(
+        &instruction,
+        &[(stake, account)],
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_initialize_wrong_owner() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let account = Account {
+        owner: Pubkey::new_unique(),
+        ..uninitialized_account(rent_exempt_reserve())
+    };
+
+    let instruction = ixn::initialize(&stake, &authorized(), &Lockup::default());
+    env.process(
+        &instruction,
+        &[(stake, account)],
+        &[Check::err(ProgramError::InvalidAccountOwner)],
+    );
+}
+
+#[test]
+fn test_initialize_truncated_data() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let mut instruction = ixn::initialize(&stake, &authorized(), &Lockup::default());
+    instruction.data.pop();
+
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve()))];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
+
+#[test]
+fn test_initialize_not_enough_accounts() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let mut instruction = ixn::initialize(&stake, &authorized(), &Lockup::default());
+    instruction.accounts.truncate(1);
+
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve()))];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
@@ -0,0 +1,86 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction as ixn,
+        state::{Authorized, Lockup, Meta, StakeStateV2},
+    },
+};
+
+use crate::fixtures::*;
+
+fn authorized() -> Authorized {
+    Authorized {
+        staker: Pubkey::new_unique(),
+        withdrawer: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn test_initialize_checked() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let authorized = authorized();
+
+    let instruction = ixn::initialize_checked(&stake, &authorized);
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve()))];
+    let result = env.process(&instruction, &accounts, &[Check::success()]);
+
+    assert_eq!(
+        stake_state(&result, &stake),
+        StakeStateV2::Initialized(Meta {
+            rent_exempt_reserve: rent_exempt_reserve(),
+            authorized,
+            lockup: Lockup::default(),
+        })
+    );
+}
+
+#[test]
+fn test_initialize_checked_withdrawer_not_signer() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let authorized = authorized();
+
+    let mut instruction = ixn::initialize_checked(&stake, &authorized);
+    instruction.accounts[3] = AccountMeta::new_readonly(authorized.withdrawer, false);
+
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve()))];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_initialize_checked_insufficient_funds() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let instruction = ixn::initialize_checked(&stake, &authorized());
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve() - 1))];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+}
+
+#[test]
+fn test_initialize_checked_not_enough_accounts() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let mut instruction = ixn::initialize_checked(&stake, &authorized());
+    instruction.accounts.truncate(3);
+
+    let accounts = [(stake, uninitialized_account(rent_exempt_reserve()))];
+    env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
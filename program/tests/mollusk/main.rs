@@ -0,0 +1,51 @@
+//! Tests of every stake instruction against the SBF build of the program.
+//!
+//! Run with `cargo build-sbf && cargo test --features test-sbf`.
+
+// without the feature nothing here runs, so say so instead of passing silently
+#[cfg(not(feature = "test-sbf"))]
+#[test]
+#[ignore = "needs the SBF build: `cargo build-sbf && cargo test --features test-sbf`"]
+fn test_sbf() {}
+
+#[cfg(feature = "test-sbf")]
+mod fixtures;
+
+#[cfg(feature = "test-sbf")]
+mod authorize;
+#[cfg(feature = "test-sbf")]
+mod authorize_checked;
+#[cfg(feature = "test-sbf")]
+mod authorize_checked_with_seed;
+#[cfg(feature = "test-sbf")]
+mod authorize_with_seed;
+#[cfg(feature = "test-sbf")]
+mod deactivate;
+#[cfg(feature = "test-sbf")]
+mod deactivate_delinquent;
+#[cfg(feature = "test-sbf")]
+mod delegate;
+#[cfg(feature = "test-sbf")]
+mod entrypoint;
+#[cfg(feature = "test-sbf")]
+mod get_minimum_delegation;
+#[cfg(feature = "test-sbf")]
+mod initialize;
+#[cfg(feature = "test-sbf")]
+mod initialize_checked;
+#[cfg(feature = "test-sbf")]
+mod merge;
+#[cfg(feature = "test-sbf")]
+mod move_lamports;
+#[cfg(feature = "test-sbf")]
+mod move_stake;
+#[cfg(feature = "test-sbf")]
+mod redelegate;
+#[cfg(feature = "test-sbf")]
+mod set_lockup;
+#[cfg(feature = "test-sbf")]
+mod set_lockup_checked;
+#[cfg(feature = "test-sbf")]
+mod split;
+#[cfg(feature = "test-sbf")]
+mod withdraw;
@@ -0,0 +1,207 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        stake_flags::StakeFlags,
+        state::StakeStateV2,
+    },
+};
+
+use crate::fixtures::*;
+
+struct Setup {
+    env: Env,
+    destination: Pubkey,
+    source: Pubkey,
+    staker: Pubkey,
+    voter: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            destination: Pubkey::new_unique(),
+            source: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+        }
+    }
+
+    fn delegated(&self, amount: u64, activation_epoch: u64, deactivation_epoch: u64) -> Account {
+        let mut stake = stake(&self.voter, amount, activation_epoch);
+        stake.delegation.deactivation_epoch = deactivation_epoch;
+        delegated_account(meta(&self.staker), stake, rent_exempt_reserve() + amount)
+    }
+
+    fn merge(&self, destination: Account, source: Account, checks: &[Check]) -> InstructionResult {
+        let instruction = ixn::merge(&self.destination, &self.source, &self.staker)
+            .pop()
+            .unwrap();
+        self.env.process(
+            &instruction,
+            &[(self.destination, destination), (self.source, source)],
+            checks,
+        )
+    }
+
+    fn check_source_drained(&self, result: &InstructionResult) {
+        assert_eq!(lamports(result, &self.source), 0);
+        assert_eq!(
+            stake_state(result, &self.source),
+            StakeStateV2::Uninitialized
+        );
+    }
+}
+
+#[test]
+fn test_merge_inactive() {
+    let setup = Setup::new();
+    let destination = initialized_account(meta(&setup.staker), rent_exempt_reserve() + 100);
+    let source = setup.delegated(200, 0, 5);
+
+    let result = setup.merge(
+        destination,
+        source,
+        &[
+            Check::success(),
+            Check::account(&setup.destination)
+                .lamports(rent_exempt_reserve() * 2 + 300)
+                .build(),
+        ],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Initialized(meta(&setup.staker))
+    );
+    setup.check_source_drained(&result);
+}
+
+#[test]
+fn test_merge_fully_active() {
+    let setup = Setup::new();
+
+    let result = setup.merge(
+        setup.delegated(1_000, 0, u64::MAX),
+        setup.delegated(2_000, 5, u64::MAX),
+        &[
+            Check::success(),
+            Check::account(&setup.destination)
+                .lamports(rent_exempt_reserve() * 2 + 3_000)
+                .build(),
+        ],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Stake(
+            meta(&setup.staker),
+            stake(&setup.voter, 3_000, 0),
+            StakeFlags::empty()
+        )
+    );
+    setup.check_source_drained(&result);
+}
+
+#[test]
+fn test_merge_activation_epoch() {
+    let setup = Setup::new();
+    let destination = setup.delegated(1_000, 10, u64::MAX);
+
+    // activating stake takes all of the source's lamports, reserve included
+    for source in [
+        setup.delegated(2_000, 10, u64::MAX),
+        initialized_account(meta(&setup.staker), rent_exempt_reserve() + 2_000),
+    ] {
+        let result = setup.merge(destination.clone(), source, &[Check::success()]);
+
+        assert_eq!(
+            stake_state(&result, &setup.destination),
+            StakeStateV2::Stake(
+                meta(&setup.staker),
+                stake(&setup.voter, rent_exempt_reserve() + 3_000, 10),
+                StakeFlags::empty()
+            )
+        );
+        setup.check_source_drained(&result);
+    }
+}
+
+#[test]
+fn test_merge_transient_stake() {
+    let setup = Setup::new();
+
+    // deactivating this epoch
+    setup.merge(
+        setup.delegated(1_000, 0, u64::MAX),
+        setup.delegated(1_000, 0, 10),
+        &[Check::err(StakeError::MergeTransientStake.into())],
+    );
+    setup.merge(
+        setup.delegated(1_000, 0, 10),
+        setup.delegated(1_000, 0, u64::MAX),
+        &[Check::err(StakeError::MergeTransientStake.into())],
+    );
+}
+
+#[test]
+fn test_merge_mismatch() {
+    let setup = Setup::new();
+
+    // different voters
+    let source = stake(&Pubkey::new_unique(), 1_000, 0);
+    setup.merge(
+        setup.delegated(1_000, 0, u64::MAX),
+        delegated_account(meta(&setup.staker), source, rent_exempt_reserve() + 1_000),
+        &[Check::err(StakeError::MergeMismatch.into())],
+    );
+
+    // different authorities
+    setup.merge(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve()),
+        initialized_account(meta(&Pubkey::new_unique()), rent_exempt_reserve()),
+        &[Check::err(StakeError::MergeMismatch.into())],
+    );
+
+    // active stake into an inactive account
+    setup.merge(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve()),
+        setup.delegated(1_000, 0, u64::MAX),
+        &[Check::err(StakeError::MergeMismatch.into())],
+    );
+}
+
+#[test]
+fn test_merge_missing_signature() {
+    let setup = Setup::new();
+    let instruction = ixn::merge(&setup.destination, &setup.source, &Pubkey::new_unique())
+        .pop()
+        .unwrap();
+
+    setup.env.process(
+        &instruction,
+        &[
+            (setup.destination, setup.delegated(1_000, 0, u64::MAX)),
+            (setup.source, setup.delegated(1_000, 0, u64::MAX)),
+        ],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_merge_into_itself() {
+    let setup = Setup::new();
+    let instruction = ixn::merge(&setup.destination, &setup.destination, &setup.staker)
+        .pop()
+        .unwrap();
+
+    setup.env.process(
+        &instruction,
+        &[(setup.destination, setup.delegated(1_000, 0, u64::MAX))],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}
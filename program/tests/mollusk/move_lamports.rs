@@ -0,0 +1,183 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::instruction::{self as ixn, StakeError},
+};
+
+use crate::fixtures::*;
+
+const STAKE_AMOUNT: u64 = 1_000_000;
+const FREE_LAMPORTS: u64 = 500;
+
+struct Setup {
+    env: Env,
+    source: Pubkey,
+    destination: Pubkey,
+    staker: Pubkey,
+    voter: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+        }
+    }
+
+    fn delegated(&self, activation_epoch: u64, deactivation_epoch: u64) -> Account {
+        let mut stake = stake(&self.voter, STAKE_AMOUNT, activation_epoch);
+        stake.delegation.deactivation_epoch = deactivation_epoch;
+        delegated_account(
+            meta(&self.staker),
+            stake,
+            rent_exempt_reserve() + STAKE_AMOUNT + FREE_LAMPORTS,
+        )
+    }
+
+    fn inactive(&self) -> Account {
+        initialized_account(meta(&self.staker), rent_exempt_reserve() + FREE_LAMPORTS)
+    }
+
+    fn move_lamports(
+        &self,
+        source: Account,
+        destination: Account,
+        lamports: u64,
+        checks: &[Check],
+    ) -> InstructionResult {
+        self.env.process(
+            &ixn::move_lamports(&self.source, &self.destination, &self.staker, lamports),
+            &[(self.source, source), (self.destination, destination)],
+            checks,
+        )
+    }
+}
+
+#[test]
+fn test_move_lamports() {
+    let setup = Setup::new();
+
+    for (source, destination) in [
+        (setup.delegated(0, u64::MAX), setup.inactive()),
+        (setup.inactive(), setup.delegated(0, u64::MAX)),
+        // the destination may be in any mergeable state
+        (setup.inactive(), setup.delegated(10, u64::MAX)),
+    ] {
+        let source_lamports = source.lamports;
+        let destination_lamports = destination.lamports;
+        let source_state = source.data.clone();
+
+        setup.move_lamports(
+            source,
+            destination,
+            FREE_LAMPORTS,
+            &[
+                Check::success(),
+                Check::account(&setup.source)
+                    .lamports(source_lamports - FREE_LAMPORTS)
+                    .data(&source_state)
+                    .build(),
+                Check::account(&setup.destination)
+                    .lamports(destination_lamports + FREE_LAMPORTS)
+                    .build(),
+            ],
+        );
+    }
+}
+
+#[test]
+fn test_move_lamports_invalid_amount() {
+    let setup = Setup::new();
+
+    for (source, lamports) in [
+        (setup.delegated(0, u64::MAX), 0),
+        // delegated lamports can't be moved
+        (setup.delegated(0, u64::MAX), FREE_LAMPORTS + 1),
+        // nor can the reserve
+        (setup.inactive(), FREE_LAMPORTS + 1),
+    ] {
+        setup.move_lamports(
+            source,
+            setup.inactive(),
+            lamports,
+            &[Check::err(ProgramError::InvalidArgument)],
+        );
+    }
+}
+
+#[test]
+fn test_move_lamports_source_not_mergeable() {
+    let setup = Setup::new();
+
+    // activating
+    setup.move_lamports(
+        setup.delegated(10, u64::MAX),
+        setup.inactive(),
+        FREE_LAMPORTS,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+    // deactivating
+    setup.move_lamports(
+        setup.delegated(0, 10),
+        setup.inactive(),
+        FREE_LAMPORTS,
+        &[Check::err(StakeError::MergeTransientStake.into())],
+    );
+}
+
+#[test]
+fn test_move_lamports_invalid_accounts() {
+    let setup = Setup::new();
+    let accounts = [
+        (setup.source, setup.inactive()),
+        (setup.destination, setup.inactive()),
+    ];
+
+    let mut instruction = ixn::move_lamports(
+        &setup.source,
+        &setup.destination,
+        &setup.staker,
+        FREE_LAMPORTS,
+    );
+    instruction.accounts[2] = AccountMeta::new_readonly(setup.staker, false);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+
+    let mut instruction = ixn::move_lamports(
+        &setup.source,
+        &setup.destination,
+        &setup.staker,
+        FREE_LAMPORTS,
+    );
+    instruction.accounts[0] = AccountMeta::new_readonly(setup.source, false);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    let instruction =
+        ixn::move_lamports(&setup.source, &setup.source, &setup.staker, FREE_LAMPORTS);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    setup.move_lamports(
+        setup.inactive(),
+        initialized_account(meta(&Pubkey::new_unique()), rent_exempt_reserve()),
+        FREE_LAMPORTS,
+        &[Check::err(StakeError::MergeMismatch.into())],
+    );
+}
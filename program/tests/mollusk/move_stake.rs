@@ -0,0 +1,250 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        stake_flags::StakeFlags,
+        state::StakeStateV2,
+    },
+};
+
+use crate::fixtures::*;
+
+const STAKE_AMOUNT: u64 = 1_000_000;
+
+struct Setup {
+    env: Env,
+    source: Pubkey,
+    destination: Pubkey,
+    staker: Pubkey,
+    voter: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+        }
+    }
+
+    fn delegated(&self, voter: &Pubkey, activation_epoch: u64, deactivation_epoch: u64) -> Account {
+        let mut stake = stake(voter, STAKE_AMOUNT, activation_epoch);
+        stake.delegation.deactivation_epoch = deactivation_epoch;
+        delegated_account(
+            meta(&self.staker),
+            stake,
+            rent_exempt_reserve() + STAKE_AMOUNT,
+        )
+    }
+
+    fn active(&self) -> Account {
+        self.delegated(&self.voter, 0, u64::MAX)
+    }
+
+    fn move_stake(
+        &self,
+        source: Account,
+        destination: Account,
+        lamports: u64,
+        checks: &[Check],
+    ) -> InstructionResult {
+        self.env.process(
+            &ixn::move_stake(&self.source, &self.destination, &self.staker, lamports),
+            &[(self.source, source), (self.destination, destination)],
+            checks,
+        )
+    }
+}
+
+#[test]
+fn test_move_stake_to_active() {
+    let setup = Setup::new();
+    let lamports = STAKE_AMOUNT / 4;
+
+    let result = setup.move_stake(
+        setup.active(),
+        setup.active(),
+        lamports,
+        &[
+            Check::success(),
+            Check::account(&setup.source)
+                .lamports(rent_exempt_reserve() + STAKE_AMOUNT - lamports)
+                .build(),
+            Check::account(&setup.destination)
+                .lamports(rent_exempt_reserve() + STAKE_AMOUNT + lamports)
+                .build(),
+        ],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.source),
+        StakeStateV2::Stake(
+            meta(&setup.staker),
+            stake(&setup.voter, STAKE_AMOUNT - lamports, 0),
+            StakeFlags::empty()
+        )
+    );
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Stake(
+            meta(&setup.staker),
+            stake(&setup.voter, STAKE_AMOUNT + lamports, 0),
+            StakeFlags::empty()
+        )
+    );
+}
+
+#[test]
+fn test_move_stake_to_inactive() {
+    let setup = Setup::new();
+
+    // moving everything leaves the source initialized
+    let result = setup.move_stake(
+        setup.active(),
+        initialized_account(meta(&setup.staker), rent_exempt_reserve()),
+        STAKE_AMOUNT,
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.source),
+        StakeStateV2::Initialized(meta(&setup.staker))
+    );
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Stake(
+            meta(&setup.staker),
+            stake(&setup.voter, STAKE_AMOUNT, 0),
+            StakeFlags::empty()
+        )
+    );
+}
+
+#[test]
+fn test_move_stake_invalid_amount() {
+    let setup = Setup::new();
+
+    for lamports in [0, STAKE_AMOUNT + 1] {
+        setup.move_stake(
+            setup.active(),
+            setup.active(),
+            lamports,
+            &[Check::err(ProgramError::InvalidArgument)],
+        );
+    }
+}
+
+#[test]
+fn test_move_stake_vote_address_mismatch() {
+    let setup = Setup::new();
+
+    setup.move_stake(
+        setup.active(),
+        setup.delegated(&Pubkey::new_unique(), 0, u64::MAX),
+        STAKE_AMOUNT / 2,
+        &[Check::err(StakeError::VoteAddressMismatch.into())],
+    );
+}
+
+#[test]
+fn test_move_stake_source_not_fully_active() {
+    let setup = Setup::new();
+
+    // activating
+    setup.move_stake(
+        setup.delegated(&setup.voter, 10, u64::MAX),
+        setup.active(),
+        STAKE_AMOUNT / 2,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+    // inactive
+    setup.move_stake(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve() + STAKE_AMOUNT),
+        setup.active(),
+        STAKE_AMOUNT / 2,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+    // deactivating
+    setup.move_stake(
+        setup.delegated(&setup.voter, 0, 10),
+        setup.active(),
+        STAKE_AMOUNT / 2,
+        &[Check::err(StakeError::MergeTransientStake.into())],
+    );
+}
+
+#[test]
+fn test_move_stake_destination_activating() {
+    let setup = Setup::new();
+
+    setup.move_stake(
+        setup.active(),
+        setup.delegated(&setup.voter, 10, u64::MAX),
+        STAKE_AMOUNT / 2,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_move_stake_authority_mismatch() {
+    let setup = Setup::new();
+
+    setup.move_stake(
+        setup.active(),
+        delegated_account(
+            meta(&Pubkey::new_unique()),
+            stake(&setup.voter, STAKE_AMOUNT, 0),
+            rent_exempt_reserve() + STAKE_AMOUNT,
+        ),
+        STAKE_AMOUNT / 2,
+        &[Check::err(StakeError::MergeMismatch.into())],
+    );
+}
+
+#[test]
+fn test_move_stake_invalid_accounts() {
+    let setup = Setup::new();
+    let accounts = [
+        (setup.source, setup.active()),
+        (setup.destination, setup.active()),
+    ];
+
+    let mut instruction = ixn::move_stake(&setup.source, &setup.destination, &setup.staker, 1);
+    instruction.accounts[2] = AccountMeta::new_readonly(setup.staker, false);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+
+    let mut instruction = ixn::move_stake(&setup.source, &setup.destination, &setup.staker, 1);
+    instruction.accounts[1] = AccountMeta::new_readonly(setup.destination, false);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    let instruction = ixn::move_stake(&setup.source, &setup.source, &setup.staker, 1);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+
+    // a signer that isn't the staker
+    let other = Pubkey::new_unique();
+    let instruction = ixn::move_stake(&setup.source, &setup.destination, &other, 1);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
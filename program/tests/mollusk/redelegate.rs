@@ -0,0 +1,34 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{program_error::ProgramError, pubkey::Pubkey, stake::instruction as ixn};
+
+use crate::fixtures::*;
+
+#[test]
+#[allow(deprecated)]
+fn test_redelegate_is_disabled() {
+    let env = Env::at_epoch(10);
+    let stake = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+    let vote = Pubkey::new_unique();
+    let uninitialized = Pubkey::new_unique();
+
+    let instruction = ixn::redelegate(&stake, &staker, &vote, &uninitialized)
+        .pop()
+        .unwrap();
+    env.process(
+        &instruction,
+        &[
+            (
+                stake,
+                delegated_account(
+                    meta(&staker),
+                    crate::fixtures::stake(&Pubkey::new_unique(), 1_000, 0),
+                    rent_exempt_reserve() + 1_000,
+                ),
+            ),
+            (uninitialized, uninitialized_account(0)),
+            (vote, vote_account(&[])),
+        ],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
@@ -0,0 +1,149 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, LockupArgs},
+        stake_flags::StakeFlags,
+        state::{Authorized, Lockup, Meta, StakeStateV2},
+    },
+};
+
+use crate::fixtures::*;
+
+struct Setup {
+    env: Env,
+    stake: Pubkey,
+    staker: Pubkey,
+    withdrawer: Pubkey,
+    custodian: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            stake: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            withdrawer: Pubkey::new_unique(),
+            custodian: Pubkey::new_unique(),
+        }
+    }
+
+    fn meta(&self, lockup_epoch: u64) -> Meta {
+        Meta {
+            authorized: Authorized {
+                staker: self.staker,
+                withdrawer: self.withdrawer,
+            },
+            lockup: Lockup {
+                unix_timestamp: 0,
+                epoch: lockup_epoch,
+                custodian: self.custodian,
+            },
+            ..meta(&self.staker)
+        }
+    }
+
+    fn set_lockup(&self, account: Account, signer: &Pubkey, checks: &[Check]) -> InstructionResult {
+        let args = LockupArgs {
+            unix_timestamp: Some(100),
+            epoch: Some(20),
+            custodian: None,
+        };
+        self.env.process(
+            &ixn::set_lockup(&self.stake, &args, signer),
+            &[(self.stake, account)],
+            checks,
+        )
+    }
+}
+
+#[test]
+fn test_set_lockup_by_withdrawer() {
+    let setup = Setup::new();
+
+    // the lockup expired, so the withdrawer is in charge
+    let result = setup.set_lockup(
+        initialized_account(setup.meta(10), rent_exempt_reserve()),
+        &setup.withdrawer,
+        &[Check::success()],
+    );
+    assert_eq!(
+        stake_state(&result, &setup.stake).lockup().unwrap(),
+        Lockup {
+            unix_timestamp: 100,
+            epoch: 20,
+            custodian: setup.custodian,
+        }
+    );
+}
+
+#[test]
+fn test_set_lockup_by_custodian() {
+    let setup = Setup::new();
+    let voter = Pubkey::new_unique();
+    let account = stake_account(
+        &StakeStateV2::Stake(setup.meta(11), stake(&voter, 1_000, 0), StakeFlags::empty()),
+        rent_exempt_reserve() + 1_000,
+    );
+
+    let result = setup.set_lockup(account, &setup.custodian, &[Check::success()]);
+    assert_eq!(
+        stake_state(&result, &setup.stake).lockup().unwrap().epoch,
+        20
+    );
+}
+
+#[test]
+fn test_set_lockup_missing_signature() {
+    let setup = Setup::new();
+
+    // only the custodian can change a lockup in force
+    setup.set_lockup(
+        initialized_account(setup.meta(11), rent_exempt_reserve()),
+        &setup.withdrawer,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+    // and only the withdrawer once it isn't
+    setup.set_lockup(
+        initialized_account(setup.meta(10), rent_exempt_reserve()),
+        &setup.custodian,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+    setup.set_lockup(
+        initialized_account(setup.meta(10), rent_exempt_reserve()),
+        &setup.staker,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_set_lockup_uninitialized() {
+    let setup = Setup::new();
+
+    setup.set_lockup(
+        uninitialized_account(rent_exempt_reserve()),
+        &setup.withdrawer,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_set_lockup_invalid_args() {
+    let setup = Setup::new();
+
+    let mut instruction = ixn::set_lockup(&setup.stake, &LockupArgs::default(), &setup.withdrawer);
+    // the first `Option` tag follows the discriminator
+    instruction.data[4] = 2;
+
+    setup.env.process(
+        &instruction,
+        &[(
+            setup.stake,
+            initialized_account(setup.meta(10), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::InvalidInstructionData)],
+    );
+}
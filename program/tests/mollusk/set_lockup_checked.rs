@@ -0,0 +1,88 @@
+use mollusk_svm::result::Check;
+use solana_sdk::{
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, LockupArgs},
+        state::Lockup,
+    },
+};
+
+use crate::fixtures::*;
+
+#[test]
+fn test_set_lockup_checked() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let withdrawer = Pubkey::new_unique();
+    let custodian = Pubkey::new_unique();
+
+    let args = LockupArgs {
+        unix_timestamp: None,
+        epoch: Some(5),
+        custodian: Some(custodian),
+    };
+    let result = env.process(
+        &ixn::set_lockup_checked(&stake, &args, &withdrawer),
+        &[(
+            stake,
+            initialized_account(meta(&withdrawer), rent_exempt_reserve()),
+        )],
+        &[Check::success()],
+    );
+
+    assert_eq!(
+        stake_state(&result, &stake).lockup().unwrap(),
+        Lockup {
+            unix_timestamp: 0,
+            epoch: 5,
+            custodian,
+        }
+    );
+}
+
+#[test]
+fn test_set_lockup_checked_custodian_not_signer() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+    let withdrawer = Pubkey::new_unique();
+    let custodian = Pubkey::new_unique();
+
+    let args = LockupArgs {
+        unix_timestamp: None,
+        epoch: Some(5),
+        custodian: Some(custodian),
+    };
+    let mut instruction = ixn::set_lockup_checked(&stake, &args, &withdrawer);
+    instruction.accounts[2] = AccountMeta::new_readonly(custodian, false);
+
+    env.process(
+        &instruction,
+        &[(
+            stake,
+            initialized_account(meta(&withdrawer), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_set_lockup_checked_missing_signature() {
+    let env = Env::new();
+    let stake = Pubkey::new_unique();
+
+    let args = LockupArgs {
+        unix_timestamp: Some(1),
+        epoch: None,
+        custodian: None,
+    };
+    env.process(
+        &ixn::set_lockup_checked(&stake, &args, &Pubkey::new_unique()),
+        &[(
+            stake,
+            initialized_account(meta(&Pubkey::new_unique()), rent_exempt_reserve()),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
@@ -0,0 +1,278 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        stake_flags::StakeFlags,
+        state::StakeStateV2,
+    },
+};
+
+use crate::fixtures::*;
+
+const STAKE_AMOUNT: u64 = 1_000_000;
+
+struct Setup {
+    env: Env,
+    source: Pubkey,
+    destination: Pubkey,
+    staker: Pubkey,
+    voter: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            staker: Pubkey::new_unique(),
+            voter: Pubkey::new_unique(),
+        }
+    }
+
+    fn instruction(&self, authority: &Pubkey, lamports: u64) -> Instruction {
+        ixn::split(&self.source, authority, lamports, &self.destination)
+            .pop()
+            .unwrap()
+    }
+
+    /// fully active, with `extra` undelegated lamports on top of the reserve
+    fn active_account(&self, extra: u64) -> Account {
+        delegated_account(
+            meta(&self.staker),
+            stake(&self.voter, STAKE_AMOUNT, 0),
+            rent_exempt_reserve() + STAKE_AMOUNT + extra,
+        )
+    }
+
+    fn split(
+        &self,
+        source: Account,
+        destination: Account,
+        lamports: u64,
+        checks: &[Check],
+    ) -> InstructionResult {
+        self.env.process(
+            &self.instruction(&self.staker, lamports),
+            &[(self.source, source), (self.destination, destination)],
+            checks,
+        )
+    }
+}
+
+#[test]
+fn test_split_initialized() {
+    let setup = Setup::new();
+    let source_lamports = rent_exempt_reserve() * 2 + 100;
+    let split_lamports = rent_exempt_reserve() + 50;
+
+    let result = setup.split(
+        initialized_account(meta(&setup.staker), source_lamports),
+        uninitialized_account(0),
+        split_lamports,
+        &[
+            Check::success(),
+            Check::account(&setup.source)
+                .lamports(source_lamports - split_lamports)
+                .build(),
+            Check::account(&setup.destination)
+                .lamports(split_lamports)
+                .build(),
+        ],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.source),
+        StakeStateV2::Initialized(meta(&setup.staker))
+    );
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Initialized(meta(&setup.staker))
+    );
+}
+
+#[test]
+fn test_split_active() {
+    let setup = Setup::new();
+    let split_lamports = STAKE_AMOUNT * 2 / 5;
+
+    let result = setup.split(
+        setup.active_account(0),
+        uninitialized_account(rent_exempt_reserve()),
+        split_lamports,
+        &[
+            Check::success(),
+            Check::account(&setup.source)
+                .lamports(rent_exempt_reserve() + STAKE_AMOUNT - split_lamports)
+                .build(),
+            Check::account(&setup.destination)
+                .lamports(rent_exempt_reserve() + split_lamports)
+                .build(),
+        ],
+    );
+
+    let mut source_stake = stake(&setup.voter, STAKE_AMOUNT - split_lamports, 0);
+    assert_eq!(
+        stake_state(&result, &setup.source),
+        StakeStateV2::Stake(meta(&setup.staker), source_stake, StakeFlags::empty())
+    );
+    source_stake.delegation.stake = split_lamports;
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Stake(meta(&setup.staker), source_stake, StakeFlags::empty())
+    );
+}
+
+#[test]
+fn test_split_all() {
+    let setup = Setup::new();
+
+    // active stake may move entirely into an unfunded account
+    let result = setup.split(
+        setup.active_account(0),
+        uninitialized_account(0),
+        rent_exempt_reserve() + STAKE_AMOUNT,
+        &[
+            Check::success(),
+            Check::account(&setup.source).lamports(0).build(),
+        ],
+    );
+
+    assert_eq!(
+        stake_state(&result, &setup.source),
+        StakeStateV2::Uninitialized
+    );
+    assert_eq!(
+        stake_state(&result, &setup.destination),
+        StakeStateV2::Stake(
+            meta(&setup.staker),
+            stake(&setup.voter, STAKE_AMOUNT, 0),
+            StakeFlags::empty()
+        )
+    );
+}
+
+#[test]
+fn test_split_active_unfunded_destination() {
+    let setup = Setup::new();
+
+    setup.split(
+        setup.active_account(0),
+        uninitialized_account(rent_exempt_reserve() - 1),
+        STAKE_AMOUNT / 2,
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+}
+
+#[test]
+fn test_split_insufficient_funds() {
+    let setup = Setup::new();
+    let source_lamports = rent_exempt_reserve() + STAKE_AMOUNT;
+
+    for split_lamports in [
+        0,
+        source_lamports + 1,
+        // leaves the source below its reserve
+        STAKE_AMOUNT + 1,
+    ] {
+        setup.split(
+            setup.active_account(0),
+            uninitialized_account(rent_exempt_reserve()),
+            split_lamports,
+            &[Check::err(ProgramError::InsufficientFunds)],
+        );
+    }
+
+    // an inactive destination must end up with its reserve
+    setup.split(
+        initialized_account(meta(&setup.staker), rent_exempt_reserve() * 3),
+        uninitialized_account(0),
+        rent_exempt_reserve() - 1,
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+}
+
+#[test]
+fn test_split_insufficient_delegation() {
+    let setup = Setup::new();
+
+    // the lamports are there, but the source would be left without stake
+    setup.split(
+        setup.active_account(10),
+        uninitialized_account(rent_exempt_reserve()),
+        STAKE_AMOUNT,
+        &[Check::err(StakeError::InsufficientDelegation.into())],
+    );
+}
+
+#[test]
+fn test_split_destination_not_uninitialized() {
+    let setup = Setup::new();
+
+    setup.split(
+        setup.active_account(0),
+        initialized_account(meta(&setup.staker), rent_exempt_reserve()),
+        STAKE_AMOUNT / 2,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_split_destination_wrong_size() {
+    let setup = Setup::new();
+    let mut destination = uninitialized_account(rent_exempt_reserve() * 2);
+    destination.data.push(0);
+
+    setup.split(
+        setup.active_account(0),
+        destination,
+        STAKE_AMOUNT / 2,
+        &[Check::err(ProgramError::InvalidAccountData)],
+    );
+}
+
+#[test]
+fn test_split_missing_signature() {
+    let setup = Setup::new();
+
+    setup.env.process(
+        &setup.instruction(&Pubkey::new_unique(), STAKE_AMOUNT / 2),
+        &[
+            (setup.source, setup.active_account(0)),
+            (
+                setup.destination,
+                uninitialized_account(rent_exempt_reserve()),
+            ),
+        ],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_split_uninitialized() {
+    let setup = Setup::new();
+    let accounts = [
+        (setup.source, uninitialized_account(1_000)),
+        (setup.destination, uninitialized_account(0)),
+    ];
+
+    // an uninitialized source must sign for itself
+    setup.env.process(
+        &setup.instruction(&setup.staker, 400),
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+    setup.env.process(
+        &setup.instruction(&setup.source, 400),
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&setup.source).lamports(600).build(),
+            Check::account(&setup.destination).lamports(400).build(),
+        ],
+    );
+}
@@ -0,0 +1,389 @@
+use mollusk_svm::result::{Check, InstructionResult};
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    stake::{
+        instruction::{self as ixn, StakeError},
+        state::{Lockup, Meta, StakeStateV2},
+    },
+};
+
+use crate::fixtures::*;
+
+const STAKE_AMOUNT: u64 = 1_000_000;
+
+struct Setup {
+    env: Env,
+    stake: Pubkey,
+    withdrawer: Pubkey,
+    recipient: Pubkey,
+}
+
+impl Setup {
+    fn new() -> Self {
+        Self {
+            env: Env::at_epoch(10),
+            stake: Pubkey::new_unique(),
+            withdrawer: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+        }
+    }
+
+    fn withdraw(&self, account: Account, lamports: u64, checks: &[Check]) -> InstructionResult {
+        let instruction = ixn::withdraw(
+            &self.stake,
+            &self.withdrawer,
+            &self.recipient,
+            lamports,
+            None,
+        );
+        self.env
+            .process(&instruction, &[(self.stake, account)], checks)
+    }
+
+    fn delegated(&self, deactivation_epoch: u64, extra: u64) -> Account {
+        let mut stake = stake(&Pubkey::new_unique(), STAKE_AMOUNT, 0);
+        stake.delegation.deactivation_epoch = deactivation_epoch;
+        delegated_account(
+            meta(&self.withdrawer),
+            stake,
+            rent_exempt_reserve() + STAKE_AMOUNT + extra,
+        )
+    }
+}
+
+#[test]
+fn test_withdraw_initialized() {
+    let setup = Setup::new();
+    let account = initialized_account(meta(&setup.withdrawer), rent_exempt_reserve() + 100);
+
+    // anything above the reserve
+    let result = setup.withdraw(
+        account.clone(),
+        100,
+        &[
+            Check::success(),
+            Check::account(&setup.stake)
+                .lamports(rent_exempt_reserve())
+                .build(),
+            Check::account(&setup.recipient).lamports(100).build(),
+        ],
+    );
+    assert_eq!(
+        stake_state(&result, &setup.stake),
+        StakeStateV2::Initialized(meta(&setup.withdrawer))
+    );
+
+    // or everything, which closes the account
+    let result = setup.withdraw(
+        account,
+        rent_exempt_reserve() + 100,
+        &[
+            Check::success(),
+            Check::account(&setup.stake).lamports(0).build(),
+        ],
+    );
+    assert_eq!(
+        stake_state(&result, &setup.stake),
+        StakeStateV2::Uninitialized
+    );
+}
+
+#[test]
+fn test_withdraw_initialized_insufficient_funds() {
+    let setup = Setup::new();
+    let account = initialized_account(meta(&setup.withdrawer), rent_exempt_reserve() + 100);
+
+    for lamports in [101, rent_exempt_reserve() + 101] {
+        setup.withdraw(
+            account.clone(),
+            lamports,
+            &[Check::err(ProgramError::InsufficientFunds)],
+        );
+    }
+}
+
+#[test]
+fn test_withdraw_uninitialized() {
+    let setup = Setup::new();
+    let accounts = [(setup.stake, uninitialized_account(1_000))];
+
+    // the account itself must sign
+    let instruction = ixn::withdraw(&setup.stake, &setup.stake, &setup.recipient, 1_000, None);
+    setup.env.process(
+        &instruction,
+        &accounts,
+        &[
+            Check::success(),
+            Check::account(&setup.recipient).lamports(1_000).build(),
+        ],
+    );
+
+    setup.env.process(
+        &ixn::withdraw(
+            &setup.stake,
+            &setup.withdrawer,
+            &setup.recipient,
+            1_000,
+            None,
+        ),
+        &accounts,
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_withdraw_missing_signature() {
+    let setup = Setup::new();
+    let staker = Pubkey::new_unique();
+    let account = initialized_account(
+        Meta {
+            authorized: solana_sdk::stake::state::Authorized {
+                staker,
+                withdrawer: setup.withdrawer,
+            },
+            ..meta(&setup.withdrawer)
+        },
+        rent_exempt_reserve() + 100,
+    );
+
+    // the staker can't withdraw
+    setup.env.process(
+        &ixn::withdraw(&setup.stake, &staker, &setup.recipient, 100, None),
+        &[(setup.stake, account)],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_withdraw_authority_not_signer() {
+    let setup = Setup::new();
+    let mut instruction =
+        ixn::withdraw(&setup.stake, &setup.withdrawer, &setup.recipient, 100, None);
+    instruction.accounts[4] = AccountMeta::new_readonly(setup.withdrawer, false);
+
+    // the signature is checked before the stake account is read at all
+    let mut wrong_owner = initialized_account(meta(&setup.withdrawer), rent_exempt_reserve() + 100);
+    wrong_owner.owner = Pubkey::new_unique();
+    let rewards_pool = stake_account(&StakeStateV2::RewardsPool, rent_exempt_reserve() + 100);
+
+    for account in [wrong_owner, rewards_pool] {
+        setup.env.process(
+            &instruction,
+            &[(setup.stake, account)],
+            &[Check::err(ProgramError::MissingRequiredSignature)],
+        );
+    }
+}
+
+#[test]
+fn test_withdraw_custodian_not_signer() {
+    let setup = Setup::new();
+    let custodian = Pubkey::new_unique();
+    let mut instruction = ixn::withdraw(
+        &setup.stake,
+        &setup.withdrawer,
+        &setup.recipient,
+        100,
+        Some(&custodian),
+    );
+    instruction.accounts[5] = AccountMeta::new_readonly(custodian, false);
+
+    // even without a lockup to lift
+    setup.env.process(
+        &instruction,
+        &[(
+            setup.stake,
+            initialized_account(meta(&setup.withdrawer), rent_exempt_reserve() + 100),
+        )],
+        &[Check::err(ProgramError::MissingRequiredSignature)],
+    );
+}
+
+#[test]
+fn test_withdraw_zero() {
+    let setup = Setup::new();
+    let account = initialized_account(meta(&setup.withdrawer), rent_exempt_reserve());
+
+    setup.withdraw(
+        account,
+        0,
+        &[
+            Check::success(),
+            Check::account(&setup.stake)
+                .lamports(rent_exempt_reserve())
+                .build(),
+        ],
+    );
+}
+
+#[test]
+fn test_withdraw_active_stake() {
+    let setup = Setup::new();
+
+    // only lamports above the delegation and reserve are free
+    setup.withdraw(
+        setup.delegated(u64::MAX, 100),
+        100,
+        &[
+            Check::success(),
+            Check::account(&setup.stake)
+                .lamports(rent_exempt_reserve() + STAKE_AMOUNT)
+                .build(),
+        ],
+    );
+
+    for lamports in [101, rent_exempt_reserve() + STAKE_AMOUNT + 100] {
+        setup.withdraw(
+            setup.delegated(u64::MAX, 100),
+            lamports,
+            &[Check::err(ProgramError::InsufficientFunds)],
+        );
+    }
+
+    // still deactivating this epoch
+    setup.withdraw(
+        setup.delegated(10, 0),
+        1,
+        &[Check::err(ProgramError::InsufficientFunds)],
+    );
+}
+
+#[test]
+fn test_withdraw_deactivated_stake() {
+    let setup = Setup::new();
+
+    let result = setup.withdraw(
+        setup.delegated(9, 0),
+        rent_exempt_reserve() + STAKE_AMOUNT,
+        &[
+            Check::success(),
+            Check::account(&setup.recipient)
+                .lamports(rent_exempt_reserve() + STAKE_AMOUNT)
+                .build(),
+        ],
+    );
+    assert_eq!(
+        stake_state(&result, &setup.stake),
+        StakeStateV2::Uninitialized
+    );
+}
+
+#[test]
+fn test_withdraw_lockup() {
+    let mut setup = Setup::new();
+    let custodian = Pubkey::new_unique();
+    let account = initialized_account(
+        Meta {
+            lockup: Lockup {
+                unix_timestamp: 1_000,
+                epoch: 0,
+                custodian,
+            },
+            ..meta(&setup.withdrawer)
+        },
+        rent_exempt_reserve() + 100,
+    );
+
+    setup.withdraw(
+        account.clone(),
+        100,
+        &[Check::err(StakeError::LockupInForce.into())],
+    );
+
+    // the custodian can lift the lockup
+    let instruction = ixn::withdraw(
+        &setup.stake,
+        &setup.withdrawer,
+        &setup.recipient,
+        100,
+        Some(&custodian),
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, account.clone())],
+        &[Check::success()],
+    );
+
+    // and so does time
+    setup.env.set_unix_timestamp(1_000);
+    setup.withdraw(account, 100, &[Check::success()]);
+}
+
+#[test]
+fn test_withdraw_custodian_is_a_signer() {
+    let setup = Setup::new();
+    let custodian = Pubkey::new_unique();
+    let account = initialized_account(meta(&custodian), rent_exempt_reserve() + 100);
+
+    // the withdrawer is only passed as the custodian
+    let instruction = ixn::withdraw(
+        &setup.stake,
+        &setup.withdrawer,
+        &setup.recipient,
+        100,
+        Some(&custodian),
+    );
+    setup.env.process(
+        &instruction,
+        &[(setup.stake, account)],
+        &[
+            Check::success(),
+            Check::account(&setup.recipient).lamports(100).build(),
+        ],
+    );
+}
+
+#[test]
+fn test_withdraw_not_enough_accounts() {
+    let setup = Setup::new();
+    let mut instruction =
+        ixn::withdraw(&setup.stake, &setup.withdrawer, &setup.recipient, 100, None);
+    instruction.accounts.truncate(4);
+
+    setup.env.process(
+        &instruction,
+        &[(
+            setup.stake,
+            initialized_account(meta(&setup.withdrawer), rent_exempt_reserve() + 100),
+        )],
+        &[Check::err(ProgramError::NotEnoughAccountKeys)],
+    );
+}
+
+#[test]
+fn test_withdraw_fake_clock() {
+    let setup = Setup::new();
+
+    // a zeroed clock would say epoch 0, when the stake has no effective stake
+    // yet, letting all of it be withdrawn
+    let fake_clock = Pubkey::new_unique();
+    let mut instruction = ixn::withdraw(
+        &setup.stake,
+        &setup.withdrawer,
+        &setup.recipient,
+        STAKE_AMOUNT,
+        None,
+    );
+    instruction.accounts[2].pubkey = fake_clock;
+
+    setup.env.process(
+        &instruction,
+        &[
+            (setup.stake, setup.delegated(u64::MAX, 0)),
+            (
+                fake_clock,
+                Account {
+                    lamports: 1,
+                    data: vec![0; 40],
+                    owner: solana_sdk::sysvar::ID,
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            ),
+        ],
+        &[Check::err(ProgramError::InvalidArgument)],
+    );
+}